@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use crate::sim::audio::{self, Bus, SoundGroup, VariantPicker, Variation};
+use crate::sim::music::{Crossfade, MusicIntensity};
+use crate::sim::rng::GameRng;
+use crate::storage;
+
+use godot::classes::{AudioStream, AudioStreamPlayer, AudioStreamPlayer2D, Engine, INode, Node};
+use godot::prelude::*;
+
+/// Where the sound groups are loaded from.
+const SOUNDS_FILE: &str = "res://sounds.toml";
+
+/// How many sound effects can play at the same time.
+const VOICE_COUNT: usize = 16;
+
+/// How many positional sound effects can play at the same time, on top of `VOICE_COUNT`.
+const POSITIONAL_VOICE_COUNT: usize = 8;
+
+/// Distance in pixels from the listener at which positional sounds fade out completely.
+/// Mobs spawn just off screen, so this reaches past the far corner of the arena.
+const POSITIONAL_MAX_DISTANCE: f32 = 1400.0;
+
+/// How steeply positional sounds get quieter with distance; 1 is linear.
+const POSITIONAL_ATTENUATION: f32 = 1.5;
+
+/// Volume in decibels of a music track faded all the way out.
+const SILENT_DB: f32 = -80.0;
+
+/// One of the two music players, with the volume and pitch its track was started at.
+struct MusicDeck {
+    player: Gd<AudioStreamPlayer>,
+    volume_db: f32,
+    pitch_scale: f32,
+}
+
+impl MusicDeck {
+    /// Sets the volume to `volume` (linear, 0 to 1) of the track's own volume.
+    fn set_fade(&mut self, volume: f32) {
+        let fade_db = if volume > 0.0 { 20.0 * volume.log10() } else { SILENT_DB };
+        self.player.set_volume_db((self.volume_db + fade_db).max(SILENT_DB));
+    }
+}
+
+/// A registered sound group with its variants loaded.
+struct LoadedGroup {
+    group: SoundGroup,
+    streams: Vec<Gd<AudioStream>>,
+    picker: VariantPicker,
+}
+
+/// Plays the game's sounds by group name, e.g. "fire" or "death".
+///
+/// Sound effects share a pool of `AudioStreamPlayer` voices and get a random variant,
+/// pitch and volume on every playback. Music plays on two voices of its own, so one
+/// track can crossfade into the next, and speeds up with the intensity set by the game.
+/// Sounds played with `play_at` use a pool of `AudioStreamPlayer2D` voices instead,
+/// panned and attenuated relative to the current `AudioListener2D` (the player's).
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct AudioManager {
+    groups: HashMap<String, LoadedGroup>,
+    // The players shared by every sound effect.
+    voices: Vec<Gd<AudioStreamPlayer>>,
+    // The voice to take over next when every voice is busy.
+    next_voice: usize,
+    // The players for positional sound effects, and the next one to take over.
+    positional_voices: Vec<Gd<AudioStreamPlayer2D>>,
+    next_positional_voice: usize,
+    // The two players for the music, which are never taken over by a sound effect,
+    // and the one playing the current track.
+    music_decks: Vec<MusicDeck>,
+    current_deck: usize,
+    // The crossfade from the other deck to the current one, while it lasts.
+    crossfade: Option<Crossfade>,
+    // How intense the current track plays, which sets its tempo.
+    intensity: MusicIntensity,
+
+    /// Pans and attenuates the sounds played with `play_at`.
+    /// Turn it off for mono setups to play them like any other sound.
+    #[export]
+    positional: bool,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for AudioManager {
+    // This function initializes a new AudioManager instance.
+    // The voices are created and the sound groups loaded once it is in the scene tree.
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            groups: HashMap::new(),
+            voices: Vec::new(),
+            next_voice: 0,
+            positional_voices: Vec::new(),
+            next_positional_voice: 0,
+            music_decks: Vec::new(),
+            current_deck: 0,
+            crossfade: None,
+            intensity: MusicIntensity::default(),
+            positional: true,
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        for index in 0..VOICE_COUNT {
+            let mut voice = AudioStreamPlayer::new_alloc();
+            voice.set_name(&format!("Voice{index}"));
+            self.base_mut().add_child(&voice);
+            self.voices.push(voice);
+        }
+
+        for index in 0..POSITIONAL_VOICE_COUNT {
+            let mut voice = AudioStreamPlayer2D::new_alloc();
+            voice.set_name(&format!("PositionalVoice{index}"));
+            voice.set_max_distance(POSITIONAL_MAX_DISTANCE);
+            voice.set_attenuation(POSITIONAL_ATTENUATION);
+            self.base_mut().add_child(&voice);
+            self.positional_voices.push(voice);
+        }
+
+        for index in 0..2 {
+            let mut player = AudioStreamPlayer::new_alloc();
+            player.set_name(&format!("Music{index}"));
+            self.base_mut().add_child(&player);
+            self.music_decks.push(MusicDeck {
+                player,
+                volume_db: 0.0,
+                pitch_scale: 1.0,
+            });
+        }
+
+        for group in Self::load_sound_groups() {
+            self.register(group);
+        }
+    }
+
+    fn process(&mut self, delta: f64) {
+        // Fade and change tempo in real time, so slow motion does not drag them out.
+        let time_scale = Engine::singleton().get_time_scale().max(0.01);
+        let delta = (delta / time_scale) as f32;
+
+        let playback_rate = {
+            self.intensity.step(delta);
+            self.intensity.playback_rate()
+        };
+        let current = self.current_deck;
+        let Some(deck) = self.music_decks.get_mut(current) else {
+            return;
+        };
+        let pitch_scale = deck.pitch_scale * playback_rate;
+        deck.player.set_pitch_scale(pitch_scale);
+
+        let Some(crossfade) = &mut self.crossfade else {
+            return;
+        };
+        let finished = crossfade.step(delta);
+        let (fading_out, fading_in) = crossfade.volumes();
+        let previous = (current + 1) % self.music_decks.len();
+        self.music_decks[previous].set_fade(fading_out);
+        self.music_decks[current].set_fade(fading_in);
+
+        if finished {
+            self.music_decks[previous].player.stop();
+            self.crossfade = None;
+        }
+    }
+}
+
+#[godot_api]
+impl AudioManager {
+    /// Adds a sound group, loading its streams and taking those of its nodes.
+    /// A group with the same name is replaced.
+    pub fn register(&mut self, group: SoundGroup) {
+        let mut streams: Vec<Gd<AudioStream>> = group
+            .streams
+            .iter()
+            .filter_map(|path| {
+                let stream = try_load::<AudioStream>(path.as_str()).ok();
+                if stream.is_none() {
+                    godot_warn!("Sound [{}] could not load {path}", group.name);
+                }
+                stream
+            })
+            .collect();
+
+        // The nodes are looked up next to the audio manager, under the Main node.
+        let parent = self.base().get_parent();
+        for name in &group.nodes {
+            let stream = parent
+                .as_ref()
+                .and_then(|parent| parent.get_node_or_null(name.as_str()))
+                .and_then(|node| node.try_cast::<AudioStreamPlayer>().ok())
+                .and_then(|player| player.get_stream());
+            match stream {
+                Some(stream) => streams.push(stream),
+                None => godot_warn!("Sound [{}] has no stream in node \"{name}\"", group.name),
+            }
+        }
+
+        self.groups.insert(
+            group.name.clone(),
+            LoadedGroup {
+                group,
+                streams,
+                picker: VariantPicker::default(),
+            },
+        );
+    }
+
+    /// Plays a random variant of the sound group `name` on a free voice.
+    /// The variant, pitch and volume are rolled with `rng`.
+    ///
+    /// Returns `false` if there is no such group or it has no streams.
+    pub fn play(&mut self, name: &str, rng: &mut GameRng) -> bool {
+        let Some((stream, variation, bus)) = self.next_playback(name, rng) else {
+            return false;
+        };
+        let Some(mut voice) = Self::free_voice(
+            &self.voices,
+            &mut self.next_voice,
+            |voice| voice.is_playing(),
+        ) else {
+            return false;
+        };
+        voice.set_stream(&stream);
+        voice.set_pitch_scale(variation.pitch_scale);
+        voice.set_volume_db(variation.volume_db);
+        voice.set_bus(bus.name());
+        voice.play();
+        true
+    }
+
+    /// Plays the sound group `name` as if it came from `position`, in global coordinates.
+    ///
+    /// Falls back to `play` while `positional` is off.
+    pub fn play_at(&mut self, name: &str, position: Vector2, rng: &mut GameRng) -> bool {
+        if !self.positional {
+            return self.play(name, rng);
+        }
+
+        let Some((stream, variation, bus)) = self.next_playback(name, rng) else {
+            return false;
+        };
+        let Some(mut voice) = Self::free_voice(
+            &self.positional_voices,
+            &mut self.next_positional_voice,
+            |voice| voice.is_playing(),
+        ) else {
+            return false;
+        };
+        voice.set_global_position(position);
+        voice.set_stream(&stream);
+        voice.set_pitch_scale(variation.pitch_scale);
+        voice.set_volume_db(variation.volume_db);
+        voice.set_bus(bus.name());
+        voice.play();
+        true
+    }
+
+    /// Starts the sound group `name` as music, crossfading from the music that is playing
+    /// over `fade` seconds. The new track starts at normal intensity.
+    pub fn play_music(&mut self, name: &str, fade: f32, rng: &mut GameRng) -> bool {
+        let Some((stream, variation, bus)) = self.next_playback(name, rng) else {
+            return false;
+        };
+        if self.music_decks.is_empty() {
+            return false;
+        }
+
+        // The new track goes on the deck that is not playing the current one.
+        let previous = self.current_deck;
+        self.current_deck = (self.current_deck + 1) % self.music_decks.len();
+
+        let deck = &mut self.music_decks[self.current_deck];
+        deck.volume_db = variation.volume_db;
+        deck.pitch_scale = variation.pitch_scale;
+        deck.player.set_stream(&stream);
+        deck.player.set_pitch_scale(variation.pitch_scale);
+        deck.player.set_bus(bus.name());
+        deck.set_fade(if fade > 0.0 { 0.0 } else { 1.0 });
+        deck.player.play();
+
+        if fade > 0.0 {
+            self.crossfade = Some(Crossfade::new(fade));
+        } else {
+            self.music_decks[previous].player.stop();
+            self.crossfade = None;
+        }
+        self.intensity = MusicIntensity::default();
+        true
+    }
+
+    /// Sets how intense the music should play, from 0 (calm) to 1.
+    /// The tempo eases towards it rather than jumping.
+    pub fn set_music_intensity(&mut self, intensity: f32) {
+        self.intensity.set_target(intensity);
+    }
+
+    /// Turns the panning and attenuation of `play_at` on or off.
+    pub fn set_positional(&mut self, positional: bool) {
+        self.positional = positional;
+    }
+
+    /// Forgets the variants played last, so a run started from a seed picks the same sounds every time.
+    pub fn reset_variants(&mut self) {
+        for loaded in self.groups.values_mut() {
+            loaded.picker = VariantPicker::default();
+        }
+    }
+
+    /// Picks the variant, pitch and volume of the next playback of the sound group `name`.
+    fn next_playback(&mut self, name: &str, rng: &mut GameRng) -> Option<(Gd<AudioStream>, Variation, Bus)> {
+        let Some(loaded) = self.groups.get_mut(name) else {
+            godot_warn!("Unknown sound \"{name}\"");
+            return None;
+        };
+        let index = loaded.picker.pick(loaded.streams.len(), rng)?;
+        let variation = loaded.group.variation(rng);
+        Some((loaded.streams[index].clone(), variation, loaded.group.bus))
+    }
+
+    /// A voice of `voices` that is not playing. If all are busy, they are taken over in turn,
+    /// starting at `next`.
+    fn free_voice<T: GodotClass>(
+        voices: &[Gd<T>],
+        next: &mut usize,
+        is_playing: impl Fn(&Gd<T>) -> bool,
+    ) -> Option<Gd<T>> {
+        if let Some(voice) = voices.iter().find(|voice| !is_playing(voice)) {
+            return Some(voice.clone());
+        }
+
+        let voice = voices.get(*next)?.clone();
+        *next = (*next + 1) % voices.len();
+        Some(voice)
+    }
+
+    /// Loads the sound groups, or none if the file is unusable.
+    fn load_sound_groups() -> Vec<SoundGroup> {
+        let text = storage::read_bytes(SOUNDS_FILE)
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+
+        let Some(text) = text else {
+            godot_warn!("Could not read {SOUNDS_FILE}, the game will be silent");
+            return Vec::new();
+        };
+
+        match audio::parse_sound_groups(&text) {
+            Ok(groups) => groups,
+            Err(error) => {
+                godot_error!("Invalid {SOUNDS_FILE}: {error}");
+                Vec::new()
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::sim::bindings::{self, Action, Binding, Bindings};
+use crate::storage;
+
+use godot::classes::{DisplayServer, InputEvent, InputEventJoypadButton, InputEventKey, InputMap, Os};
+use godot::global::{JoyButton, Key};
+use godot::obj::EngineEnum;
+use godot::prelude::*;
+
+/// Where the player's key bindings are saved.
+pub const BINDINGS_FILE: &str = "user://bindings.toml";
+
+/// The bindings set up in the input map of `project.godot`.
+///
+/// Call this before `apply`, which replaces them.
+pub fn defaults() -> Bindings {
+    let input_map = InputMap::singleton();
+    let mut defaults = Bindings::default();
+    for action in Action::ALL {
+        if !input_map.has_action(action.name()) {
+            godot_warn!("Input action \"{}\" not found", action.name());
+            continue;
+        }
+        for event in input_map.action_get_events(action.name()).iter_shared() {
+            if let Some(binding) = binding_of(&event) {
+                defaults.bind(action, binding);
+            }
+        }
+    }
+    defaults
+}
+
+/// Loads the saved bindings, falling back to `defaults` if there are none or the file is unusable.
+pub fn load(defaults: &Bindings) -> Bindings {
+    let Some(bytes) = storage::read_bytes(BINDINGS_FILE) else {
+        return defaults.clone();
+    };
+
+    let parsed = String::from_utf8(bytes)
+        .map_err(|error| error.to_string())
+        .and_then(|text| Bindings::from_config(&text, defaults).map_err(|error| error.to_string()));
+
+    match parsed {
+        Ok(bindings) => bindings,
+        Err(error) => {
+            godot_warn!("Could not load {BINDINGS_FILE}: {error}, using the default bindings");
+            defaults.clone()
+        }
+    }
+}
+
+pub fn save(bindings: &Bindings) {
+    storage::write_bytes(BINDINGS_FILE, bindings.to_config().as_bytes());
+}
+
+/// Replaces the key and gamepad button events of every action in the input map with `bindings`.
+/// Other events, like the gamepad stick axes, are kept.
+///
+/// Everything reads input through the input map, so the new bindings apply straight away.
+pub fn apply(bindings: &Bindings) {
+    let mut input_map = InputMap::singleton();
+    for action in Action::ALL {
+        if !input_map.has_action(action.name()) {
+            input_map.add_action(action.name());
+        }
+        for event in input_map.action_get_events(action.name()).iter_shared() {
+            if binding_of(&event).is_some() {
+                input_map.action_erase_event(action.name(), &event);
+            }
+        }
+        for binding in bindings.get(action) {
+            if let Some(event) = event_of(*binding) {
+                input_map.action_add_event(action.name(), &event);
+            }
+        }
+    }
+}
+
+/// The binding for a key or gamepad button event, or `None` for any other input.
+pub fn binding_of(event: &Gd<InputEvent>) -> Option<Binding> {
+    if let Ok(key) = event.clone().try_cast::<InputEventKey>() {
+        let code = key.get_physical_keycode();
+        return (code != Key::NONE).then(|| Binding::Key(code.ord()));
+    }
+
+    if let Ok(button) = event.clone().try_cast::<InputEventJoypadButton>() {
+        return Some(Binding::Button(button.get_button_index().ord()));
+    }
+
+    None
+}
+
+/// An input event that matches `binding` on any device.
+fn event_of(binding: Binding) -> Option<Gd<InputEvent>> {
+    match binding {
+        Binding::Key(code) => {
+            let mut event = InputEventKey::new_gd();
+            event.set_device(-1);
+            event.set_physical_keycode(Key::try_from_ord(code)?);
+            Some(event.upcast())
+        }
+        Binding::Button(index) => {
+            let mut event = InputEventJoypadButton::new_gd();
+            event.set_device(-1);
+            event.set_button_index(JoyButton::try_from_ord(index)?);
+            Some(event.upcast())
+        }
+    }
+}
+
+/// A name for the binding to show to the player, e.g. "Q" or "Gamepad A".
+///
+/// Keys are named after what they type on the current keyboard layout.
+pub fn describe(binding: Binding) -> String {
+    match binding {
+        Binding::Key(code) => {
+            let Some(key) = Key::try_from_ord(code) else {
+                return format!("Key {code}");
+            };
+            let label = DisplayServer::singleton().keyboard_get_label_from_physical(key);
+            Os::singleton().get_keycode_string(label).to_string()
+        }
+        Binding::Button(index) => bindings::button_name(index),
+    }
+}
@@ -0,0 +1,17 @@
+use crate::sim::Vec2;
+
+use godot::prelude::*;
+
+// Conversions between the simulation's vector type and Godot's `Vector2`.
+
+impl From<Vec2> for Vector2 {
+    fn from(v: Vec2) -> Self {
+        Vector2::new(real::from_f32(v.x), real::from_f32(v.y))
+    }
+}
+
+impl From<Vector2> for Vec2 {
+    fn from(v: Vector2) -> Self {
+        Vec2::new(v.x.as_f32(), v.y.as_f32())
+    }
+}
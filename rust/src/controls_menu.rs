@@ -0,0 +1,234 @@
+use crate::bindings;
+use crate::sim::bindings::{Action, Binding, Bindings};
+
+use godot::classes::{
+    Button, HBoxContainer, IPanelContainer, InputEvent, Label, PanelContainer, VBoxContainer,
+};
+use godot::global::Key;
+use godot::obj::EngineEnum;
+use godot::prelude::*;
+
+
+#[derive(GodotClass)]
+#[class(base=PanelContainer)]
+pub struct ControlsMenu {
+    bindings: Bindings,
+    // The bindings of `project.godot`, which "Reset" goes back to.
+    defaults: Bindings,
+    // The action waiting for the next key or gamepad button, if any.
+    capturing: Option<Action>,
+    // The labels listing the bindings of each action, in the order of `Action::ALL`.
+    binding_labels: Vec<Gd<Label>>,
+    status_label: OnReady<Gd<Label>>,
+    base: Base<PanelContainer>,
+}
+
+#[godot_api]
+impl IPanelContainer for ControlsMenu {
+    // This function initializes a new ControlsMenu instance.
+    // The bindings are loaded in `ready`, once the input map is set up.
+    fn init(base: Base<PanelContainer>) -> Self {
+        Self {
+            bindings: Bindings::default(),
+            defaults: Bindings::default(),
+            capturing: None,
+            binding_labels: Vec::new(),
+            status_label: OnReady::manual(),
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // Remember the bindings of the input map before replacing them with the saved ones.
+        self.defaults = bindings::defaults();
+        self.bindings = bindings::load(&self.defaults);
+        bindings::apply(&self.bindings);
+
+        // Build the menu: a row for every action with its bindings and buttons to add or
+        // clear them, a line for messages, and buttons to reset the bindings and go back.
+        let mut rows = VBoxContainer::new_alloc();
+        rows.add_theme_constant_override("separation", 8);
+
+        let mut title = Label::new_alloc();
+        title.set_text("Controls");
+        title.set_horizontal_alignment(godot::global::HorizontalAlignment::CENTER);
+        rows.add_child(&title);
+
+        // The index of the action is bound to the callbacks, so one method handles every row.
+        for action in Action::ALL {
+            let index = (action.index() as i64).to_variant();
+
+            let mut name_label = Label::new_alloc();
+            name_label.set_text(&action.to_string());
+            name_label.set_custom_minimum_size(Vector2::new(120.0, 0.0));
+
+            let mut binding_label = Label::new_alloc();
+            binding_label.set_h_size_flags(godot::classes::control::SizeFlags::EXPAND_FILL);
+            binding_label.set_custom_minimum_size(Vector2::new(140.0, 0.0));
+            binding_label.set_autowrap_mode(godot::classes::text_server::AutowrapMode::WORD_SMART);
+
+            let mut add_button = Button::new_alloc();
+            add_button.set_text("Add");
+            add_button.connect(
+                "pressed",
+                &self.base().callable("on_add_pressed").bind(&[index.clone()]),
+            );
+
+            let mut clear_button = Button::new_alloc();
+            clear_button.set_text("Clear");
+            clear_button.connect(
+                "pressed",
+                &self.base().callable("on_clear_pressed").bind(&[index]),
+            );
+
+            let mut row = HBoxContainer::new_alloc();
+            row.add_child(&name_label);
+            row.add_child(&binding_label);
+            row.add_child(&add_button);
+            row.add_child(&clear_button);
+            rows.add_child(&row);
+            self.binding_labels.push(binding_label);
+        }
+
+        let mut status_label = Label::new_alloc();
+        status_label.set_autowrap_mode(godot::classes::text_server::AutowrapMode::WORD_SMART);
+        status_label.set_custom_minimum_size(Vector2::new(0.0, 56.0));
+        rows.add_child(&status_label);
+        self.status_label.init(status_label);
+
+        let mut reset_button = Button::new_alloc();
+        reset_button.set_text("Reset");
+        reset_button.connect("pressed", &self.base().callable("on_reset_pressed"));
+
+        let mut back_button = Button::new_alloc();
+        back_button.set_text("Back");
+        back_button.connect("pressed", &self.base().callable("on_back_pressed"));
+
+        let mut buttons = HBoxContainer::new_alloc();
+        buttons.add_child(&reset_button);
+        buttons.add_child(&back_button);
+        rows.add_child(&buttons);
+
+        self.base_mut().add_child(&rows);
+        self.refresh();
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        // Only listen while waiting for a new binding, and only to presses.
+        let Some(action) = self.capturing else {
+            return;
+        };
+        if !event.is_pressed() || event.is_echo() {
+            return;
+        }
+        let Some(binding) = bindings::binding_of(&event) else {
+            return;
+        };
+
+        // The key or button is taken by the menu, so it does not press a button or start a game too.
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.set_input_as_handled();
+        }
+        self.capturing = None;
+
+        // Escape cancels, so it cannot be bound.
+        if binding == Binding::Key(Key::ESCAPE.ord()) {
+            self.set_status("Cancelled.".to_string());
+            return;
+        }
+
+        // A key or button can only trigger one action, so it is taken away from any other.
+        let message = match self.bindings.bind(action, binding) {
+            Some(other) => format!(
+                "{} now triggers {action} instead of {other}.",
+                bindings::describe(binding)
+            ),
+            None => format!("{} now triggers {action}.", bindings::describe(binding)),
+        };
+        self.set_status(message);
+        self.apply();
+    }
+}
+
+#[godot_api]
+impl ControlsMenu {
+    /// Emitted when the player closes the menu, which is when the bindings are saved.
+    #[signal]
+    pub fn closed();
+
+    /// This function shows the menu with the current bindings.
+    pub fn open(&mut self) {
+        self.capturing = None;
+        self.set_status(String::new());
+        self.refresh();
+        self.base_mut().show();
+    }
+
+    /// This function applies the bindings to the input map and lists them in the menu.
+    fn apply(&mut self) {
+        bindings::apply(&self.bindings);
+        self.refresh();
+    }
+
+    /// This function lists the bindings of every action, marking the actions left without one.
+    fn refresh(&mut self) {
+        for (action, label) in Action::ALL.into_iter().zip(self.binding_labels.iter_mut()) {
+            let names: Vec<String> = self
+                .bindings
+                .get(action)
+                .iter()
+                .map(|binding| bindings::describe(*binding))
+                .collect();
+            if names.is_empty() {
+                label.set_text("(none)");
+            } else {
+                label.set_text(&names.join(", "));
+            }
+        }
+    }
+
+    fn set_status(&mut self, message: String) {
+        self.status_label.set_text(&message);
+    }
+
+    #[func]
+    /// This function is called when an "Add" button is pressed.
+    /// The next key or gamepad button pressed is bound to the action of its row.
+    fn on_add_pressed(&mut self, action: i64) {
+        let Some(action) = Action::from_index(action) else {
+            return;
+        };
+        self.capturing = Some(action);
+        self.set_status(format!("Press a key or gamepad button for {action}, or Escape to cancel."));
+    }
+
+    #[func]
+    /// This function is called when a "Clear" button is pressed. It removes every binding of the row's action.
+    fn on_clear_pressed(&mut self, action: i64) {
+        let Some(action) = Action::from_index(action) else {
+            return;
+        };
+        self.capturing = None;
+        self.bindings.clear(action);
+        self.set_status(format!("{action} has no bindings left."));
+        self.apply();
+    }
+
+    #[func]
+    /// This function is called when the "Reset" button is pressed. It brings back the default bindings.
+    fn on_reset_pressed(&mut self) {
+        self.capturing = None;
+        self.bindings = self.defaults.clone();
+        self.set_status("Default bindings restored.".to_string());
+        self.apply();
+    }
+
+    #[func]
+    /// This function is called when the "Back" button is pressed. It saves the bindings and hides the menu.
+    fn on_back_pressed(&mut self) {
+        self.capturing = None;
+        bindings::save(&self.bindings);
+        self.base_mut().hide();
+        self.signals().closed().emit();
+    }
+}
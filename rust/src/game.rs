@@ -1,36 +1,124 @@
-use crate::{mob, player, hud};
+use crate::{mob, pickup, player, hud, settings, storage};
+use crate::audio::AudioManager;
+use crate::mob_pool::MobPool;
+use crate::sim::archetype::{self, MobArchetype, MovementPattern};
+use crate::sim::arena::{self, Arena};
+use crate::sim::death;
+use crate::sim::difficulty::{Difficulty, DifficultyCurve, DifficultyPreset};
+use crate::sim::graze::GrazeCombo;
+use crate::sim::highscore::{self, HighScore, HighScoreTable};
+use crate::sim::milestone::{self, Milestone, MilestoneReward, MilestoneTable};
+use crate::sim::music;
+use crate::sim::powerup::{self, PowerUpKind, PowerUps};
+use crate::sim::replay::Replay;
+use crate::sim::rng::GameRng;
+use crate::sim::score::Score;
+use crate::sim::settings::Settings;
+use crate::sim::shake::{self, ScreenShake};
+use crate::sim::spawn::{self, MobSpawn, PerimeterPath, SpawnParams};
+use crate::sim::Vec2;
 
-use godot::classes::{Marker2D, PathFollow2D, RigidBody2D, Timer};
+use godot::classes::timer::TimerProcessCallback;
+use godot::classes::notify::NodeNotification;
+use godot::classes::{Engine, Marker2D, Path2D, Timer};
 use godot::prelude::*;
 
-use rand::seq::IndexedMutRandom;
-use rand::Rng as _;
-use std::f32::consts::PI;
+/// Every run is recorded to this file and overwritten by the next one.
+const LAST_RUN_REPLAY: &str = "user://last_run.replay";
+
+/// The mob types that can spawn, see the comments in the file for the format.
+const MOB_TYPES_FILE: &str = "res://mob_types.toml";
+
+/// Where the score milestones are loaded from.
+const MILESTONES_FILE: &str = "res://milestones.toml";
+
+/// Where the high score table is saved.
+const HIGH_SCORES_FILE: &str = "user://highscores.dat";
+
+/// Mobs instantiated up front, enough for the busiest moments of a hard run.
+const MOB_POOL_SIZE: usize = 48;
+
+/// Seconds between power-up pickups.
+const PICKUP_INTERVAL: f64 = 8.0;
+
+/// Pickups spawn at least this far from the edges of the arena.
+const PICKUP_MARGIN: f32 = 48.0;
+
+/// Seconds the menu music takes to crossfade into the game music when a run starts.
+const MUSIC_FADE: f32 = 0.5;
+
+/// The stream of the run's seed that sound variations are rolled from, see `GameRng::substream`.
+const SOUND_STREAM: u64 = 1;
+
+/// Points for every mob the player dashes through.
+const DASH_NEAR_MISS_BONUS: i32 = 3;
 
 
 #[derive(GodotClass)]
 #[class(base=Node)]
 pub struct GameScene {
     mob_scene: OnReady<Gd<PackedScene>>,
+    pickup_scene: OnReady<Gd<PackedScene>>,
     player: OnReady<Gd<player::Player>>,
     hud: OnReady<Gd<hud::HUD>>,
-    music: OnReady<Gd<AudioStreamPlayer>>,
-    death_sound: OnReady<Gd<AudioStreamPlayer>>,
+    audio: OnReady<Gd<AudioManager>>,
+
+    score: Score,
+    milestones: MilestoneTable,
+    // Points the difficulty curve has been moved ahead by milestones this run.
+    difficulty_bump: i32,
+    power_ups: PowerUps,
+    graze: GrazeCombo,
+    mob_pool: MobPool,
+    arena: Arena,
+    // The points of the "MobPath" curve as laid out for the design size, which `mob_path` is scaled from.
+    mob_path_points: Vec<Vec2>,
+    mob_path: PerimeterPath,
+    archetypes: Vec<MobArchetype>,
+    difficulty_preset: DifficultyPreset,
+    difficulty: DifficultyCurve,
+    settings: Settings,
+    shake: ScreenShake,
+
+    /// Seed for the run's random number generator.
+    /// Leave it at 0 to pick a new random seed every game, or set it to the
+    /// seed shown on the game over screen to replay that run.
+    #[export]
+    seed: i64,
+    rng: GameRng,
+    // Rolls the sound variations from the same seed, without taking numbers from `rng`.
+    sound_rng: GameRng,
+
+    /// Replay file to play back on every new game instead of reading the keyboard.
+    /// Its seed takes precedence over `seed`. Leave it empty to play normally.
+    #[export]
+    replay_path: GString,
+    // Whether the current run is a replay being played back, which does not make the high score table.
+    playing_replay: bool,
 
-    fire_sound: OnReady<Gd<AudioStreamPlayer>>,
-    fire_sound2: OnReady<Gd<AudioStreamPlayer>>,
-    fire_sound3: OnReady<Gd<AudioStreamPlayer>>,
-    fire_sound4: OnReady<Gd<AudioStreamPlayer>>,
-    fire_sound5: OnReady<Gd<AudioStreamPlayer>>,
+    high_scores: HighScoreTable,
+    // Rank of the record set by the last run, while its name is being entered.
+    new_record: Option<usize>,
+    // The name entered for the last record, offered again for the next one.
+    player_name: String,
 
-    score5: OnReady<Gd<AudioStreamPlayer>>,
-    score10: OnReady<Gd<AudioStreamPlayer>>,
-    score20: OnReady<Gd<AudioStreamPlayer>>,
-    score25: OnReady<Gd<AudioStreamPlayer>>,
-    score35: OnReady<Gd<AudioStreamPlayer>>,
-    score50: OnReady<Gd<AudioStreamPlayer>>,
+    /// Shows the number of live mobs in the corner of the screen, for debugging.
+    #[export]
+    show_mob_count: bool,
 
-    score: i32,
+    /// Pans and attenuates the fireball sounds by where the mobs come in, relative to the player.
+    /// Turn it off for mono setups. Players can also turn it off in the settings menu.
+    #[export]
+    positional_audio: bool,
+
+    /// Pauses the game when the window or browser tab loses focus during a run.
+    #[export]
+    pause_on_focus_loss: bool,
+
+    /// Keeps the arena to the portrait shape of the design size on wider screens,
+    /// with bars at the sides. Turn it off to let the arena fill landscape screens.
+    #[export]
+    letterbox: bool,
 
     base: Base<Node>,
 }
@@ -44,42 +132,146 @@ impl INode for GameScene {
     /// It returns a new instance of `GameScene` with the following properties:
     ///
     /// - `mob_scene`: A reference to the "res://mob.tscn" scene which is used to create new mob instances.
+    /// - `pickup_scene`: A reference to the "res://pickup.tscn" scene which is used to create power-up pickups.
     /// - `player`: A reference to the "Player" node which is the player character.
     /// - `hud`: A reference to the "HUD" node which is the heads-up display.
-    /// - `music`: A reference to the "GameSoundTrack" node which is the background music.
-    /// - `death_sound`: A reference to the "DeathSound" node which is the sound effect played when the player dies.
-    /// - `fire_sound`, `fire_sound2`, `fire_sound3`, `fire_sound4`, `fire_sound5`: A reference to the "FireSound", "FireSound2", "FireSound3", "FireSound4" and "FireSound5" nodes which are the sound effects played when the player fires their weapon.
-    /// - `score5`, `score10`, `score20`, `score25`, `score35`, `score50`: A reference to the "Score5", "Score10", "Score20", "Score25", "Score35" and "Score50" nodes which are the sound effects played when the player scores 5, 10, 20, 25, 35 and 50 points respectively.
-    /// - `score`: An integer which is the current score of the player.
+    /// - `audio`: The "AudioManager" node, created in `ready`, which plays the music and every sound effect
+    ///   by the names of the sound groups in "res://sounds.toml".
+    /// - `score`: The current score of the player.
+    /// - `milestones`: The score milestones, loaded from "res://milestones.toml".
+    /// - `difficulty_bump`: How far milestones have moved the difficulty curve ahead of the score.
+    /// - `power_ups`: The power-ups the player has collected that are still running.
+    /// - `graze`: The combo of mobs that passed close to the player without hitting them.
+    /// - `mob_pool`: Mob instances waiting to be spawned again.
+    /// - `arena`: The play area, fitted to the visible screen in `ready` and whenever the window is resized.
+    /// - `mob_path_points`: The points of the "MobPath" curve, read once the scene is ready.
+    /// - `mob_path`: The perimeter mobs spawn on, the "MobPath" curve scaled to the arena.
+    /// - `archetypes`: The kinds of mob that can spawn, loaded from "res://mob_types.toml".
+    /// - `difficulty_preset`: The difficulty preset of the current run.
+    /// - `difficulty`: The difficulty curve of that preset, keyed on the score.
+    /// - `settings`: The player's settings, loaded from and saved to "user://settings.toml".
+    /// - `shake`: The screen shake set off by hits and bombs.
+    /// - `seed`: The exported seed, 0 meaning a random seed every game.
+    /// - `rng`: The seeded random number generator behind every random decision in a run.
+    /// - `sound_rng`: The generator for the sound variations, derived from the same seed as `rng`.
+    /// - `replay_path`: An optional replay file to play back instead of reading the keyboard.
+    /// - `playing_replay`: Whether the current run is played back from that file.
+    /// - `high_scores`: The best scores, loaded from and saved to "user://highscores.dat".
+    /// - `new_record`: The rank of the last run's record while the player types a name for it.
+    /// - `player_name`: The name last entered for a record.
+    /// - `show_mob_count`: Whether the live mob count is shown on the HUD.
+    /// - `positional_audio`: Whether fireball sounds are heard from where the mobs come in.
+    /// - `pause_on_focus_loss`: Whether the game pauses itself when the player switches to another window or tab.
+    /// - `letterbox`: Whether the arena keeps its portrait shape on landscape screens.
     ///
     /// The `OnReady` type is used to wait for the scene to finish loading and for the nodes to be ready.
     fn init(base: Base<Node>) -> Self {
         Self {
             mob_scene: OnReady::from_loaded("res://mob.tscn"),
+            pickup_scene: OnReady::from_loaded("res://pickup.tscn"),
             player: OnReady::from_node("Player"),
             hud: OnReady::from_node("HUD"),
-            music: OnReady::from_node("GameSoundTrack"),
-            death_sound: OnReady::from_node("DeathSound"),
-            fire_sound: OnReady::from_node("FireSound"),
-            fire_sound2: OnReady::from_node("FireSound2"),
-            fire_sound3: OnReady::from_node("FireSound3"),
-            fire_sound4: OnReady::from_node("FireSound4"),
-            fire_sound5: OnReady::from_node("FireSound5"),
-            score5: OnReady::from_node("Score5"),
-            score10: OnReady::from_node("Score10"),
-            score20: OnReady::from_node("Score20"),
-            score25: OnReady::from_node("Score25"),
-            score35: OnReady::from_node("Score35"),
-            score50: OnReady::from_node("Score50"),
-            score: 0,
+            audio: OnReady::manual(),
+            score: Score::default(),
+            milestones: MilestoneTable::default(),
+            difficulty_bump: 0,
+            power_ups: PowerUps::default(),
+            graze: GrazeCombo::default(),
+            mob_pool: MobPool::default(),
+            arena: Arena::default(),
+            mob_path_points: Vec::new(),
+            mob_path: PerimeterPath::new(Vec::new()),
+            archetypes: vec![MobArchetype::default()],
+            difficulty_preset: DifficultyPreset::Normal,
+            difficulty: DifficultyPreset::Normal.curve(),
+            settings: Settings::default(),
+            shake: ScreenShake::default(),
+            seed: 0,
+            rng: GameRng::new(GameRng::random_seed()),
+            sound_rng: GameRng::new(GameRng::random_seed()),
+            replay_path: GString::new(),
+            playing_replay: false,
+            high_scores: HighScoreTable::default(),
+            new_record: None,
+            player_name: highscore::DEFAULT_NAME.to_string(),
+            show_mob_count: false,
+            positional_audio: true,
+            pause_on_focus_loss: true,
+            letterbox: true,
             base
         }
     }
 
     fn ready(&mut self) {
+        // Run the game timers in step with the physics frames.
+        // Replays record input once per physics frame, so spawns and score ticks
+        // have to land on the same frames every time for a replay to match.
+        for timer in ["StartTimer", "MobTimer", "ScoreTimer"] {
+            self.base()
+                .get_node_as::<Timer>(timer)
+                .set_timer_process_callback(TimerProcessCallback::PHYSICS);
+        }
+
+        // Create the audio manager, which loads the sound groups and plays every sound.
+        let mut audio = AudioManager::new_alloc();
+        audio.set_name("AudioManager");
+        self.base_mut().add_child(&audio);
+        self.audio.init(audio);
+
+        // Load the player's settings and apply them before anything is heard or seen.
+        self.settings = settings::load();
+        self.hud
+            .bind()
+            .settings_menu()
+            .bind_mut()
+            .set_settings(self.settings.clone());
+        self.apply_settings();
+
+        // Play the menu music while the start button is shown.
+        self.audio.bind_mut().play_music("menu", 0.0, &mut self.sound_rng);
+
+        // Create the timer that spawns power-up pickups during a run.
+        // Like the other game timers it runs in step with the physics frames.
+        let mut pickup_timer = Timer::new_alloc();
+        pickup_timer.set_name("PickupTimer");
+        pickup_timer.set_wait_time(PICKUP_INTERVAL);
+        pickup_timer.set_timer_process_callback(TimerProcessCallback::PHYSICS);
+        pickup_timer.connect("timeout", &self.base().callable("on_pickup_timer_timeout"));
+        self.base_mut().add_child(&pickup_timer);
+
+        // Copy the points of the "MobPath" curve, which the simulation's perimeter path is built from.
+        // Mobs are spawned along this path instead of moving a PathFollow2D around.
+        let mob_path = self.base().get_node_as::<Path2D>("MobPath");
+        let origin = mob_path.get_position();
+        if let Some(curve) = mob_path.get_curve() {
+            self.mob_path_points = (0..curve.get_point_count())
+                .map(|i| (origin + curve.get_point_position(i)).into())
+                .collect();
+        }
+
+        // Fit the arena to the screen now and again whenever the window is resized.
+        // The player, the mob path and the mobs all follow it.
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.connect("size_changed", &self.base().callable("on_viewport_size_changed"));
+        }
+        self.update_arena();
+
+        // Load the mob types designers can add to without touching the code.
+        self.archetypes = Self::load_archetypes();
+
+        // Load the score milestones, which can be tuned the same way.
+        self.milestones = Self::load_milestones();
+
+        // Load the saved high scores.
+        self.high_scores = Self::load_high_scores();
+
         // Get a reference to the main node.
         let main = self.to_gd();
 
+        // Instantiate the mobs up front so spawning does not have to.
+        self.mob_pool
+            .prewarm(&self.mob_scene, MOB_POOL_SIZE, |mob| Self::connect_mob(&main, mob));
+
         // Connect the "hit" signal of the player to the "game_over" method of the main node.
         // This means that when the player is hit, the game_over method will be called.
         self.player
@@ -99,36 +291,141 @@ impl INode for GameScene {
                 &main, // The object to connect the signal to.
                 Self::new_game // The method to call when the signal is emitted.
             );
+
+        // Connect the "name_entered" signal of the HUD to the "on_name_entered" method,
+        // which stores the name typed in for a new record.
+        self.hud
+            .signals()
+            .name_entered()
+            .connect_obj(&main, Self::on_name_entered);
+
+        // Connect the "death_finished" signal of the player, which shows the game over screen
+        // once the death animation has played.
+        self.player
+            .signals()
+            .death_finished()
+            .connect_obj(&main, Self::on_death_finished);
+
+        // Connect the "life_lost" signal of the player, which updates the lives on the HUD.
+        // The run only ends with the "hit" signal, once the last life is lost.
+        self.player
+            .signals()
+            .life_lost()
+            .connect_obj(&main, Self::on_life_lost);
+
+        // Connect the "shield_broken" signal of the player, which ends the shield power-up.
+        self.player
+            .signals()
+            .shield_broken()
+            .connect_obj(&main, Self::on_shield_broken);
+
+        // Connect the "dash_near_miss" signal of the player, which rewards dashing through mobs.
+        self.player
+            .signals()
+            .dash_near_miss()
+            .connect_obj(&main, Self::on_dash_near_miss);
+
+        // Connect the "grazed" signal of the player, which scores mobs that pass close by.
+        self.player
+            .signals()
+            .grazed()
+            .connect_obj(&main, Self::on_grazed);
+
+        // Apply the settings as soon as they are changed in the settings menu,
+        // and save them once it is closed.
+        let mut settings_menu = self.hud.bind().settings_menu();
+        settings_menu
+            .signals()
+            .settings_changed()
+            .connect_obj(&main, Self::on_settings_changed);
+        settings_menu
+            .signals()
+            .closed()
+            .connect_obj(&main, Self::on_settings_menu_closed);
+
+        // Connect the choices of the pause menu, which keeps working while the game is paused.
+        let mut pause_menu = self.hud.bind().pause_menu();
+        pause_menu
+            .signals()
+            .pause_requested()
+            .connect_obj(&main, Self::on_pause_requested);
+        pause_menu
+            .signals()
+            .resumed()
+            .connect_obj(&main, Self::on_resumed);
+        pause_menu
+            .signals()
+            .restart_requested()
+            .connect_obj(&main, Self::on_restart_requested);
+        pause_menu
+            .signals()
+            .quit_requested()
+            .connect_obj(&main, Self::on_quit_requested);
+    }
+
+    fn on_notification(&mut self, what: NodeNotification) {
+        // Pause when the player switches to another window or browser tab,
+        // so the run is not lost while they are away.
+        let focus_lost = matches!(
+            what,
+            NodeNotification::APPLICATION_FOCUS_OUT
+                | NodeNotification::WM_WINDOW_FOCUS_OUT
+                | NodeNotification::APPLICATION_PAUSED
+        );
+        if focus_lost && self.pause_on_focus_loss {
+            self.on_pause_requested();
+        }
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        // Count the running power-ups down in game time and undo the ones that ran out.
+        for kind in self.power_ups.step(delta as f32) {
+            self.set_power_up_effect(kind, false);
+        }
+        self.hud.bind_mut().update_power_ups(&self.power_ups);
+
+        // Let the graze combo decay when no mob has passed close by for a while.
+        self.graze.step(delta as f32);
+        self.hud.bind_mut().update_combo(&self.graze);
+
+        // Show how far the dash has recharged while the player is alive.
+        if self.run_in_progress() {
+            let readiness = self.player.bind().dash_readiness();
+            self.hud.bind_mut().update_dash_cooldown(readiness);
+        }
+    }
+
+    fn process(&mut self, delta: f64) {
+        // Shake the screen in real time, so it settles just as fast in slow motion.
+        if self.shake.is_shaking() {
+            let real_delta = delta / Engine::singleton().get_time_scale();
+            let offset = self.shake.step(real_delta as f32);
+            self.set_screen_offset(offset.into());
+        }
+
+        // Let the music speed up with the number of mobs and the difficulty during a run.
+        if self.run_in_progress() {
+            let danger = music::danger(self.live_mob_count() as usize, &self.current_difficulty());
+            self.audio.bind_mut().set_music_intensity(danger);
+        }
+
+        // Keep the debug mob counter and pool statistics up to date while they are enabled.
+        let stats = self
+            .show_mob_count
+            .then(|| (self.live_mob_count() as usize, self.mob_pool.stats()));
+        self.hud.bind_mut().show_mob_count(stats);
     }
 }
 
 #[godot_api]
 impl GameScene {
 
-    fn spawn_fireball(&mut self) {
-        // Create an array of mutable references to the fire sound nodes.
-        // These nodes correspond to different fireball sound effects in the game.
-        let mut fireballs = [
-            &mut self.fire_sound,
-            &mut self.fire_sound2,
-            &mut self.fire_sound3,
-            &mut self.fire_sound4,
-            &mut self.fire_sound5,
-        ];
-        
-        // Create a random number generator.
-        // This will be used to select a random sound from the array.
-        let mut rng = rand::rng();
-
-        // Use the 'choose_mut' method to select a random element from the 'fireballs' array.
-        // This method returns an Option containing a mutable reference to a randomly selected element.
-        if let Some(random_sound) = fireballs.choose_mut(&mut rng) {
-            // If a sound was successfully selected, play the sound.
-            // This effectively plays a random fireball sound effect.
-            random_sound.play();
-        }
-    }
-    
+    fn spawn_fireball(&mut self, position: Vector2) {
+        // Play one of the fireball sound effects, never the same one twice in a row,
+        // from where the mob comes in so the player can hear which side it is on.
+        self.audio.bind_mut().play_at("fire", position, &mut self.sound_rng);
+    }
+
     fn game_over(&mut self) {
 
         // Stop the score timer.
@@ -147,17 +444,187 @@ impl GameScene {
             .get_node_as::<Timer>("MobTimer")
             .stop();
 
-        // Show the game over screen.
+        // Stop spawning pickups and end the running power-ups.
+        self
+            .base()
+            .get_node_as::<Timer>("PickupTimer")
+            .stop();
+        self.clear_power_ups();
+
+        // The combo ends with the run.
+        self.graze.reset();
+        self.hud.bind_mut().update_combo(&self.graze);
+
+        // Add the run to the high score table if it made the cut, and save it right away
+        // so the record is kept even if the game is closed before a name is entered.
+        // A replay was already scored when it was played, so it does not count again.
+        self.new_record = if self.playing_replay {
+            None
+        } else {
+            self.high_scores.insert(HighScore {
+                name: self.player_name.clone(),
+                score: self.score.value(),
+                seed: self.rng.seed(),
+            })
+        };
+        if self.new_record.is_some() {
+            self.save_high_scores();
+        }
+
+        // Crossfade back to the menu music while the player dies, and play the death sound effect.
+        self.audio.bind_mut().play_music("menu", death::DEATH_DURATION, &mut self.sound_rng);
+        self.audio.bind_mut().play("death", &mut self.sound_rng);
+        self.add_shake(shake::HIT_TRAUMA);
+
+        // Save the seed, the difficulty and the recorded input of the run as a replay.
+        let replay = Replay {
+            seed: self.rng.seed(),
+            difficulty: self.difficulty_preset,
+            frames: self.player.bind_mut().take_recording(),
+        };
+        storage::write_bytes(LAST_RUN_REPLAY, &replay.encode());
+    }
+
+    /// Called once the player's death sequence has played out, after `game_over`.
+    fn on_death_finished(&mut self) {
+        // Show the game over screen along with the seed of the run,
+        // then the high score table and, for a new record, the name entry.
         // This is done by calling the show_game_over method on the HUD node.
-        self.hud.bind_mut().show_game_over();
+        let seed = self.rng.seed() as i64;
+        let mut hud = self.hud.bind_mut();
+        hud.show_game_over(seed);
+        hud.show_high_scores(&self.high_scores, self.new_record);
+        if self.new_record.is_some() {
+            hud.prompt_name(&self.player_name);
+        }
+    }
+
+    fn on_name_entered(&mut self, name: GString) {
+        // Only the record set by the last run can be renamed.
+        let Some(rank) = self.new_record.take() else {
+            return;
+        };
+
+        // Store the name with the record and remember it for the next one.
+        self.high_scores.rename(rank, &name.to_string());
+        self.player_name = self.high_scores.entries()[rank].name.clone();
+        self.save_high_scores();
+
+        // Refresh the table so the new name shows up.
+        self.hud
+            .bind_mut()
+            .show_high_scores(&self.high_scores, Some(rank));
+    }
+
+    /// Sets the mob spawn rate for the current score from the difficulty curve.
+    /// The speed range and heading spread are applied to each mob as it spawns.
+    fn apply_difficulty(&mut self) {
+        let difficulty = self.current_difficulty();
+        self.base()
+            .get_node_as::<Timer>("MobTimer")
+            .set_wait_time(f64::from(difficulty.mob_interval));
+    }
+
+    /// Whether a run is going on and the player is still alive.
+    fn run_in_progress(&self) -> bool {
+        self.player.is_visible() && self.player.bind().is_alive()
+    }
+
+    /// The difficulty at the current score.
+    /// Milestones can move the curve ahead of the score with a difficulty bump.
+    fn current_difficulty(&self) -> Difficulty {
+        self.difficulty.at(self.score.value() + self.difficulty_bump)
+    }
+
+    /// Loads the mob archetypes, falling back to the original fireball if the file is unusable.
+    fn load_archetypes() -> Vec<MobArchetype> {
+        let text = storage::read_bytes(MOB_TYPES_FILE)
+            .and_then(|bytes| String::from_utf8(bytes).ok());
 
-        // Stop the background music.
-        // This is done by calling the stop method on the music node.
-        self.music.stop();
+        let Some(text) = text else {
+            godot_warn!("Could not read {MOB_TYPES_FILE}, only spawning the default mob");
+            return vec![MobArchetype::default()];
+        };
 
-        // Play the death sound effect.
-        // This is done by calling the play method on the death_sound node.
-        self.death_sound.play();
+        match archetype::parse_archetypes(&text) {
+            Ok(archetypes) => archetypes,
+            Err(error) => {
+                godot_error!("Invalid {MOB_TYPES_FILE}: {error}");
+                vec![MobArchetype::default()]
+            }
+        }
+    }
+
+    /// Loads the score milestones, falling back to the original ones if the file is unusable.
+    fn load_milestones() -> MilestoneTable {
+        let text = storage::read_bytes(MILESTONES_FILE)
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+
+        let Some(text) = text else {
+            godot_warn!("Could not read {MILESTONES_FILE}, using the default milestones");
+            return MilestoneTable::default();
+        };
+
+        match milestone::parse_milestones(&text) {
+            Ok(milestones) => milestones,
+            Err(error) => {
+                godot_error!("Invalid {MILESTONES_FILE}: {error}");
+                MilestoneTable::default()
+            }
+        }
+    }
+
+    /// Loads the high score table, starting a new one if there is none.
+    ///
+    /// A file that cannot be read (corrupted, truncated or from a newer version)
+    /// is kept next to the original with a ".bad" suffix instead of being
+    /// silently overwritten by the next record.
+    fn load_high_scores() -> HighScoreTable {
+        let Some(bytes) = storage::read_bytes(HIGH_SCORES_FILE) else {
+            return HighScoreTable::default();
+        };
+
+        match HighScoreTable::decode(&bytes, highscore::DEFAULT_CAPACITY) {
+            Ok(table) => {
+                // A table in an older format was migrated while decoding.
+                // Write it back in the current format, so the migration only happens once.
+                let encoded = table.encode();
+                if encoded != bytes {
+                    storage::write_bytes(HIGH_SCORES_FILE, &encoded);
+                }
+                table
+            }
+            Err(error) => {
+                godot_warn!("Could not load {HIGH_SCORES_FILE}: {error}, starting a new table");
+                storage::write_bytes(&format!("{HIGH_SCORES_FILE}.bad"), &bytes);
+                HighScoreTable::default()
+            }
+        }
+    }
+
+    fn save_high_scores(&self) {
+        storage::write_bytes(HIGH_SCORES_FILE, &self.high_scores.encode());
+    }
+
+    /// Loads the replay set in `replay_path`, if any.
+    fn load_replay(&self) -> Option<Replay> {
+        if self.replay_path.is_empty() {
+            return None;
+        }
+
+        let path = self.replay_path.to_string();
+        let Some(bytes) = storage::read_bytes(&path) else {
+            godot_warn!("Replay {path} not found, playing normally");
+            return None;
+        };
+
+        match Replay::decode(&bytes) {
+            Ok(replay) => Some(replay),
+            Err(error) => {
+                godot_warn!("Could not load replay {path}: {error}");
+                None
+            }
+        }
     }
 
     fn new_game(&mut self) {
@@ -174,32 +641,108 @@ impl GameScene {
             .get_node_as::<Timer>("StartTimer")
             .start();
 
-        // Optionally clear all mobs from the scene.
-        // Uncomment the line below if you want to clear mobs when starting a new game.
-        // self.base().get_tree().unwrap().call_group("mobs", "queue_free", &[]);
+        // Send the mobs left over from the previous game back to the pool,
+        // and remove the pickups nobody collected.
+        self.clear_mobs();
+        self.base()
+            .get_tree()
+            .unwrap()
+            .call_group(pickup::GROUP, "queue_free", &[]);
+        self.clear_power_ups();
 
         // Reset the player's score to 0 at the start of a new game.
-        self.score = 0;
+        self.score.reset();
+        self.graze.reset();
+        self.difficulty_bump = 0;
+
+        // Let the screen settle if the last run ended mid-shake.
+        self.shake.stop();
+        self.set_screen_offset(Vector2::ZERO);
+
+        // The name entry is closed when a new game starts, so the last record keeps its name.
+        self.new_record = None;
+
+
+        // Reseed the random number generator so the run can be replayed from its seed.
+        // A replay brings its own seed, otherwise an exported seed of 0 means
+        // a fresh random seed for every game.
+        let replay = self.load_replay();
+        let seed = match &replay {
+            Some(replay) => replay.seed,
+            None if self.seed != 0 => self.seed as u64,
+            None => GameRng::random_seed(),
+        };
+        self.rng.reseed(seed);
+
+        // Roll the sounds from the same seed too, starting from fresh variants,
+        // so the run sounds the same every time it is played from this seed.
+        self.sound_rng = self.rng.substream(SOUND_STREAM);
+        self.audio.bind_mut().reset_variants();
+
+        // Use the difficulty the replay was recorded on, or the preset picked in the HUD,
+        // and start at its easiest point.
+        self.difficulty_preset = match &replay {
+            Some(replay) => replay.difficulty,
+            None => self.hud.bind().selected_difficulty(),
+        };
+        self.difficulty = self.difficulty_preset.curve();
+        self.apply_difficulty();
+
+        // Drive the player from the replay's recorded input, or from the keyboard.
+        self.playing_replay = replay.is_some();
+        self.player
+            .bind_mut()
+            .set_replay(replay.map(|replay| replay.frames));
 
         // Set the player's position to the starting position and make the player visible.
         // This ensures the player starts the game at the correct location and is visible on the screen.
-        self.player.bind_mut().start(start_position.get_position());
+        let start_position = self.arena.scale_from_design(start_position.get_position().into());
+        self.player.bind_mut().start(start_position.into());
 
         // Update the HUD to reflect the new score and show a starting message.
         // The score is reset to 0, and a message "Die!" is displayed to the player.
+        let lives = self.player.bind().lives();
         let mut hud = self.hud.bind_mut(); // Create a mutable reference to the HUD.
-        hud.update_score(self.score);
+        hud.update_score(self.score.value());
+        hud.update_lives(lives);
         hud.show_message("Die!".into());
 
         // Play the background music for the game.
         // This creates an immersive environment for the player as they play the game.
-        self.music.play();
+        self.audio.bind_mut().play_music("music", MUSIC_FADE, &mut self.sound_rng);
+    }
+
+    #[func]
+    /// This function returns the number of mobs currently in play,
+    /// not counting those on their way back to the pool.
+    pub fn live_mob_count(&self) -> i64 {
+        let Some(tree) = self.base().get_tree() else {
+            return 0;
+        };
+        tree.get_nodes_in_group(mob::GROUP)
+            .iter_shared()
+            .filter_map(|node| node.try_cast::<mob::Mob>().ok())
+            .filter(|mob| mob.bind().is_active())
+            .count() as i64
+    }
+
+    #[func]
+    /// This function returns the mob pool statistics as a dictionary with
+    /// "hits", "misses" and "idle" counts, for debugging.
+    pub fn mob_pool_stats(&self) -> Dictionary {
+        let stats = self.mob_pool.stats();
+        let mut dictionary = Dictionary::new();
+        dictionary.set("hits", stats.hits);
+        dictionary.set("misses", stats.misses);
+        dictionary.set("idle", self.mob_pool.idle_count() as i64);
+        dictionary
     }
 
     #[func]
     pub fn on_start_timer_timeout(&mut self) {
         self.base_mut().get_node_as::<Timer>("MobTimer").start();
         self.base_mut().get_node_as::<Timer>("ScoreTimer").start();
+        self.base_mut().get_node_as::<Timer>("PickupTimer").start();
     }
 
     #[func]
@@ -207,42 +750,54 @@ impl GameScene {
         // This function is called every second (because the timer interval is set to 1 second).
         // It increments the player's score by 1 and updates the HUD to reflect the new score.
 
-        // Increment the player's score.
-        self.score += 1;
+        // Increment the player's score, by more while the score multiplier is running.
+        self.add_score(self.power_ups.score_multiplier());
+    }
+
+    /// Adds points to the score, updates the HUD and the difficulty,
+    /// and sets off every milestone passed on the way.
+    fn add_score(&mut self, points: i32) {
+        let previous = self.score.value();
+        let score = self.score.add(points);
 
         // Update the HUD to reflect the new score.
-        self.hud.bind_mut().update_score(self.score);
-
-        // Play a sound effect when the player reaches certain score milestones.
-        // The sound effects are played when the player scores 5, 10, 20, 25, 35 and 50 points.
-        match self.score {
-            5 => {
-                // Play the sound effect for scoring 5 points.
-                self.score5.play();
-            },
-            10 => {
-                // Play the sound effect for scoring 10 points.
-                self.score10.play();
-            },
-            20 => {
-                // Play the sound effect for scoring 20 points.
-                self.score20.play();
-            },
-            25 => {
-                // Play the sound effect for scoring 25 points.
-                self.score25.play();
-            },
-            35 => {
-                // Play the sound effect for scoring 35 points.
-                self.score35.play();
-            },
-            50 => {
-                // Play the sound effect for scoring 50 points.
-                self.score50.play();
-            },
-            _ => {
-                // Do nothing if the player's score is not one of the milestone scores.
-            },
+        self.hud.bind_mut().update_score(score);
+
+        // A score can jump over several milestones at once, so every score passed is checked.
+        let reached: Vec<(i32, Milestone)> = self
+            .milestones
+            .reached(previous, score)
+            .into_iter()
+            .map(|(at, milestone)| (at, milestone.clone()))
+            .collect();
+        for (at, milestone) in reached {
+            self.reach_milestone(at, &milestone);
+        }
+
+        // The game gets harder as the score goes up.
+        self.apply_difficulty();
+    }
+
+    /// Plays the sound of a milestone reached at `score`, shows its message and hands out its rewards.
+    fn reach_milestone(&mut self, score: i32, milestone: &Milestone) {
+        if let Some(sound) = &milestone.sound {
+            self.audio.bind_mut().play(sound, &mut self.sound_rng);
+        }
+
+        if let Some(message) = milestone.message_at(score) {
+            self.hud.bind_mut().show_message(message.into());
+        }
+
+        self.difficulty_bump += milestone.difficulty_bump;
+
+        match milestone.reward {
+            Some(MilestoneReward::PowerUp(kind)) => self.activate_power_up(kind),
+            // Bonus points do not set off milestones of their own, so repeating ones cannot chain.
+            Some(MilestoneReward::Points(points)) => {
+                let score = self.score.add(points);
+                self.hud.bind_mut().update_score(score);
+            }
+            None => {}
         }
     }
 
@@ -252,56 +807,359 @@ impl GameScene {
         // It is responsible for spawning a new mob instance at a random location along the `MobPath`.
         // The mob is also given a random direction and speed.
 
-        // Get a reference to the `MobSpawnLocation` PathFollow2D node.
-        // This node is used to determine the position of the mob spawn location.
-        let mut mob_spawn_location = self
-            .base()
-            .get_node_as::<PathFollow2D>("MobPath/MobSpawnLocation");
+        // Pick the kind of mob to spawn, weighted by the archetypes' spawn weights
+        // among those allowed at the current score.
+        let Some(archetype) =
+            archetype::pick_archetype(&self.archetypes, self.score.value(), &mut self.rng).cloned()
+        else {
+            return;
+        };
 
-        // Instantiate a new mob scene using the `mob_scene` PackedScene.
-        // The `mob_scene` is a reference to the packed scene of the mob.
-        let mut mob_scene = self
-            .mob_scene
-            .instantiate_as::<RigidBody2D>();
+        // Scale the archetype's speed range and set the heading spread for the current difficulty.
+        let base = SpawnParams {
+            min_speed: archetype.min_speed,
+            max_speed: archetype.max_speed,
+            ..SpawnParams::default()
+        };
+        let params = self.current_difficulty().apply(&base);
 
-        // Generate a random progress value between 0.0 and 1.0.
-        // This value is used to determine the position of the mob spawn location.
-        let mut rng = rand::rng(); //adaptation to 0.9 rand
-        let progress = rng.random_range(u16::MIN..u16::MAX); //adaptation to 0.9 rand
+        // Let the simulation pick a random point along the path, a heading pointing
+        // into the screen (with some jitter) and a random speed.
+        // All of it comes from the run's seeded generator.
+        let spawn = spawn::spawn_mob(&self.mob_path, &params, &mut self.rng);
+
+        self.add_mob(&archetype, &spawn);
+
+        // Call the `spawn_fireball` function to play a random fireball sound effect.
+        self.spawn_fireball(spawn.position.into());
+    }
 
-        // Set the progress of the `MobSpawnLocation` node to the random value.
-        // This sets the position of the mob spawn location.
-        mob_spawn_location.set_progress(progress as f32);
+    /// Spawns the second half of a split mob as a straight-flying mob of the same type.
+    fn on_mob_split(&mut self, position: Vector2, velocity: Vector2, archetype: GString) {
+        let name = archetype.to_string();
+        let mut archetype = self
+            .archetypes
+            .iter()
+            .find(|archetype| archetype.name == name)
+            .cloned()
+            .unwrap_or_default();
+        archetype.movement = MovementPattern::Straight;
 
-        // Set the position of the mob scene to the position of the mob spawn location.
-        mob_scene.set_position(mob_spawn_location.get_position());
+        let spawn = MobSpawn {
+            position: position.into(),
+            direction: velocity.angle().as_f32(),
+            speed: velocity.length().as_f32(),
+        };
+        self.add_mob(&archetype, &spawn);
+    }
 
-        // Generate a random direction for the mob.
-        // The direction is a value between -PI/4 and PI/4 radians.
-        let mut direction = mob_spawn_location.get_rotation() + PI / 2.0;
-        direction += rng.random_range(-PI / 4.0..PI / 4.0);
+    /// Adds a mob of the given archetype to the scene as described by `spawn`.
+    fn add_mob(&mut self, archetype: &MobArchetype, spawn: &MobSpawn) {
+        // Take a mob from the pool, or instantiate the `mob_scene` if the pool is empty.
+        let main = self.to_gd();
+        let mut mob = self
+            .mob_pool
+            .acquire(&self.mob_scene, |mob| Self::connect_mob(&main, mob));
 
-        // Set the rotation of the mob scene to the random direction.
-        mob_scene.set_rotation(direction);
+        // Place and rotate the mob according to the spawn, and tell it where the arena ends.
+        mob.set_position(spawn.position.into());
+        mob.set_rotation(real::from_f32(spawn.direction));
+        mob.bind_mut().set_arena(self.arena);
 
-        // Add the mob scene to the scene tree.
+        // Add the mob to the scene tree and put it back into play.
         self.base_mut()
-            .add_child(&mob_scene);
-
-        // Get a reference to the mob instance.
-        let mut mob = mob_scene.cast::<mob::Mob>();
+            .add_child(&mob);
+        mob.bind_mut().activate();
 
-        // Generate a random speed value between the minimum and maximum speed of the mob.
-        let range = {
-            let mob = mob.bind();
-            rng.random_range(mob.min_speed..mob.max_speed)
-        }; //adaptation to 0.9 rand
+        // Give the mob the archetype's size, animation and movement.
+        mob.bind_mut().apply_archetype(archetype);
 
         // Set the linear velocity of the mob to the random speed value.
         // The direction of the velocity is determined by the rotation of the mob scene.
-        mob.set_linear_velocity(Vector2::new(range, 0.0).rotated(real::from_f32(direction)));
+        mob.set_linear_velocity(spawn.velocity().into());
+    }
 
-        // Call the `spawn_fireball` function to play a random fireball sound effect.
-        self.spawn_fireball();
+    /// Connects the signals of a newly instantiated mob. Pooled mobs keep their connections.
+    fn connect_mob(main: &Gd<Self>, mob: &mut Gd<mob::Mob>) {
+        // Spawn the other half when a splitting mob splits.
+        mob.signals()
+            .split()
+            .connect_obj(main, Self::on_mob_split);
+
+        // Recycle the mob once it has left the arena.
+        mob.signals()
+            .despawned()
+            .connect_obj(main, Self::on_mob_despawned);
+    }
+
+    /// Takes a mob that is out of play out of the scene tree and back into the pool.
+    ///
+    /// The mob is removed at the end of the frame, as physics bodies cannot
+    /// leave the tree while their signals are being sent.
+    fn on_mob_despawned(&mut self, mob: Gd<mob::Mob>) {
+        self.base_mut()
+            .call_deferred("release_mob", &[mob.to_variant()]);
+    }
+
+    #[func]
+    fn release_mob(&mut self, mob: Gd<mob::Mob>) {
+        if mob.get_parent().is_some() {
+            self.base_mut().remove_child(&mob);
+        }
+        self.mob_pool.release(mob);
+    }
+
+    /// Takes every mob in play out of the game and sends it back to the pool.
+    fn clear_mobs(&mut self) {
+        let mobs = self.base().get_tree().unwrap().get_nodes_in_group(mob::GROUP);
+        for mob in mobs.iter_shared() {
+            if let Ok(mut mob) = mob.try_cast::<mob::Mob>() {
+                if mob.bind().is_active() {
+                    mob.bind_mut().deactivate();
+                    self.on_mob_despawned(mob);
+                }
+            }
+        }
+    }
+
+    #[func]
+    pub fn on_pickup_timer_timeout(&mut self) {
+        // Pick a random power-up and a spot on the screen for it.
+        // Both come from the run's seeded generator, so replays get the same pickups.
+        let spawn = powerup::spawn_pickup(self.arena.size, PICKUP_MARGIN, &mut self.rng);
+
+        let mut pickup = self.pickup_scene.instantiate_as::<pickup::Pickup>();
+        pickup.set_position(spawn.position.into());
+        self.base_mut().add_child(&pickup);
+        pickup
+            .bind_mut()
+            .set_kind(spawn.kind, self.settings.colorblind_palette);
+
+        let main = self.to_gd();
+        pickup
+            .signals()
+            .collected()
+            .connect_obj(&main, Self::on_pickup_collected);
+    }
+
+    fn on_pickup_collected(&mut self, kind: i64) {
+        if let Some(kind) = PowerUpKind::from_index(kind) {
+            self.activate_power_up(kind);
+        }
+    }
+
+    /// Starts a power-up, from a pickup or a milestone reward.
+    fn activate_power_up(&mut self, kind: PowerUpKind) {
+        // Power-ups with a duration are counted down in `physics_process`,
+        // the bomb goes off straight away.
+        if self.power_ups.activate(kind) {
+            self.set_power_up_effect(kind, true);
+        } else if kind == PowerUpKind::Bomb {
+            self.clear_mobs();
+            self.add_shake(shake::BOMB_TRAUMA);
+        }
+        self.hud.bind_mut().update_power_ups(&self.power_ups);
+    }
+
+    fn on_life_lost(&mut self, remaining: i32) {
+        self.hud.bind_mut().update_lives(remaining);
+        self.add_shake(shake::HIT_TRAUMA);
+
+        // Getting hit breaks the graze combo.
+        self.graze.reset();
+        self.hud.bind_mut().update_combo(&self.graze);
+    }
+
+    fn on_grazed(&mut self) {
+        // A graze is worth more the longer the combo, and the score multiplier applies on top.
+        let points = self.graze.graze() * self.power_ups.score_multiplier();
+        self.hud.bind_mut().update_combo(&self.graze);
+        self.add_score(points);
+    }
+
+    fn on_dash_near_miss(&mut self) {
+        // Dashing through a mob earns a bonus, multiplied like the regular score.
+        let bonus = DASH_NEAR_MISS_BONUS * self.power_ups.score_multiplier();
+        self.add_score(bonus);
+    }
+
+    fn on_shield_broken(&mut self) {
+        // The player has already dropped the shield, so only the countdown is left to stop.
+        self.power_ups.deactivate(PowerUpKind::Shield);
+    }
+
+    /// Starts or ends the effect of a power-up that lasts a while.
+    fn set_power_up_effect(&mut self, kind: PowerUpKind, active: bool) {
+        let mut player = self.player.bind_mut();
+        match kind {
+            PowerUpKind::Shield => player.set_shielded(active),
+            PowerUpKind::SlowMotion => {
+                // Slow the whole game down and speed the player up to match,
+                // so only the mobs and timers are slower.
+                let time_scale = if active { powerup::SLOW_MOTION_SCALE } else { 1.0 };
+                Engine::singleton().set_time_scale(f64::from(time_scale));
+                player.set_speed_scale(1.0 / time_scale);
+            }
+            PowerUpKind::Shrink => {
+                player.set_size_scale(if active { powerup::SHRINK_SCALE } else { 1.0 });
+            }
+            // The score multiplier is read on every score tick and the bomb acts at once.
+            PowerUpKind::ScoreMultiplier | PowerUpKind::Bomb => {}
+        }
+    }
+
+    /// Ends every running power-up and its effect.
+    fn clear_power_ups(&mut self) {
+        for active in self.power_ups.active().to_vec() {
+            self.set_power_up_effect(active.kind, false);
+        }
+        self.power_ups.clear();
+        self.hud.bind_mut().update_power_ups(&self.power_ups);
+    }
+
+    /// Shakes the screen, unless the player turned screen shake off.
+    fn add_shake(&mut self, trauma: f32) {
+        if self.settings.screen_shake {
+            self.shake.add_trauma(trauma);
+        }
+    }
+
+    /// Moves everything but the HUD by `offset`, which is how the screen shakes.
+    /// The HUD is on its own canvas layer and stays put.
+    /// The offset is on top of the arena's own, which centres a letterboxed arena.
+    fn set_screen_offset(&mut self, offset: Vector2) {
+        let origin = Vector2::from(self.arena.offset) + offset;
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.set_canvas_transform(Transform2D::from_angle_origin(0.0, origin));
+        }
+    }
+
+    #[func]
+    fn on_viewport_size_changed(&mut self) {
+        self.update_arena();
+    }
+
+    /// Fits the arena to the visible screen and hands it to everything that moves in it:
+    /// the player, the mob path and the mobs in play.
+    fn update_arena(&mut self) {
+        let Some(viewport) = self.base().get_viewport() else {
+            return;
+        };
+        let screen = viewport.get_visible_rect().size.into();
+        let max_aspect = self
+            .letterbox
+            .then(|| arena::DESIGN_SIZE.x / arena::DESIGN_SIZE.y);
+        self.arena = Arena::fit(screen, max_aspect);
+
+        let points = self
+            .mob_path_points
+            .iter()
+            .map(|point| self.arena.scale_from_design(*point))
+            .collect();
+        self.mob_path = PerimeterPath::new(points);
+
+        self.player.bind_mut().set_arena(self.arena);
+        let mobs = self.base().get_tree().unwrap().get_nodes_in_group(mob::GROUP);
+        for mob in mobs.iter_shared() {
+            if let Ok(mut mob) = mob.try_cast::<mob::Mob>() {
+                mob.bind_mut().set_arena(self.arena);
+            }
+        }
+
+        // Move the arena to its new place on the screen, keeping any shake going.
+        let shake = self.shake.offset().into();
+        self.set_screen_offset(shake);
+    }
+
+    fn on_settings_changed(&mut self) {
+        self.settings = self.hud.bind().settings_menu().bind().settings().clone();
+        self.apply_settings();
+    }
+
+    fn on_settings_menu_closed(&mut self) {
+        settings::save(&self.settings);
+    }
+
+    /// Applies the settings to the engine, the sounds, the screen shake, the power-up colours
+    /// and the control mode.
+    /// Pickups already on screen keep their colour for the few seconds they have left.
+    fn apply_settings(&mut self) {
+        settings::apply(&self.settings);
+
+        let positional = self.positional_audio && self.settings.positional_audio;
+        self.audio.bind_mut().set_positional(positional);
+
+        if !self.settings.screen_shake {
+            self.shake.stop();
+            self.set_screen_offset(Vector2::ZERO);
+        }
+
+        let follow_pointer = self.settings.follow_pointer;
+        self.player.bind_mut().set_follow_pointer(follow_pointer);
+
+        let mut hud = self.hud.bind_mut();
+        hud.set_colorblind_palette(self.settings.colorblind_palette);
+        hud.set_joystick_enabled(!follow_pointer);
+    }
+
+    /// Pauses a run in progress. There is nothing to pause on the title or game over screen.
+    fn on_pause_requested(&mut self) {
+        if self.run_in_progress() && !self.is_paused() {
+            self.set_paused(true);
+        }
+    }
+
+    fn on_resumed(&mut self) {
+        self.set_paused(false);
+    }
+
+    /// Gives up the run and starts a new one straight away, without recording a high score.
+    fn on_restart_requested(&mut self) {
+        self.set_paused(false);
+        self.stop_timers();
+        self.new_game();
+    }
+
+    /// Gives up the run and goes back to the title screen, without recording a high score.
+    fn on_quit_requested(&mut self) {
+        self.set_paused(false);
+        self.stop_timers();
+
+        // Clear the arena as a game over would, and take the player off screen.
+        self.clear_mobs();
+        self.base()
+            .get_tree()
+            .unwrap()
+            .call_group(pickup::GROUP, "queue_free", &[]);
+        self.clear_power_ups();
+        self.graze.reset();
+        self.shake.stop();
+        self.set_screen_offset(Vector2::ZERO);
+        self.player.bind_mut().stop();
+
+        self.audio.bind_mut().play_music("menu", MUSIC_FADE, &mut self.sound_rng);
+
+        let mut hud = self.hud.bind_mut();
+        hud.update_combo(&self.graze);
+        hud.show_start_button();
+    }
+
+    fn is_paused(&self) -> bool {
+        self.base().get_tree().is_some_and(|tree| tree.is_paused())
+    }
+
+    /// Freezes or unfreezes the whole scene tree (timers, mobs and the player) and shows the pause menu meanwhile.
+    fn set_paused(&mut self, paused: bool) {
+        if let Some(mut tree) = self.base().get_tree() {
+            tree.set_pause(paused);
+        }
+        self.hud.bind_mut().show_pause_menu(paused);
+    }
+
+    /// Stops the timers that drive a run.
+    fn stop_timers(&mut self) {
+        for timer in ["StartTimer", "MobTimer", "ScoreTimer", "PickupTimer"] {
+            self.base().get_node_as::<Timer>(timer).stop();
+        }
     }
 }
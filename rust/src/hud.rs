@@ -1,4 +1,18 @@
-use godot::classes::{Button, CanvasLayer, ICanvasLayer, Label, Timer};
+use crate::mob_pool::PoolStats;
+use crate::controls_menu::ControlsMenu;
+use crate::pause_menu::PauseMenu;
+use crate::pickup;
+use crate::virtual_joystick::VirtualJoystick;
+use crate::settings_menu::SettingsMenu;
+use crate::sim::difficulty::DifficultyPreset;
+use crate::sim::graze::GrazeCombo;
+use crate::sim::highscore::{HighScoreTable, MAX_NAME_LENGTH};
+use crate::sim::powerup::{PowerUpKind, PowerUps};
+
+use godot::classes::{
+    Button, CanvasLayer, HBoxContainer, ICanvasLayer, Label, LineEdit, OptionButton,
+    ProgressBar, Timer, VBoxContainer,
+};
 use godot::prelude::*;
 
 
@@ -23,6 +37,66 @@ impl ICanvasLayer for HUD {
             base
         }
     }
+
+    fn ready(&mut self) {
+        // Fill the difficulty selector with the presets, in the order of `DifficultyPreset::ALL`,
+        // and select "Normal" by default.
+        let mut difficulty_button = self.base().get_node_as::<OptionButton>("DifficultyButton");
+        difficulty_button.clear();
+        for preset in DifficultyPreset::ALL {
+            difficulty_button.add_item(&preset.to_string());
+        }
+        difficulty_button.select(DifficultyPreset::Normal.index() as i32);
+
+        // Add an icon with a countdown bar to the "PowerUpBar" for every power-up that lasts a while.
+        // They stay hidden until the power-up is collected.
+        let mut power_up_bar = self.base().get_node_as::<HBoxContainer>("PowerUpBar");
+        for kind in PowerUpKind::ALL.into_iter().filter(|kind| kind.duration() > 0.0) {
+            let mut icon = Label::new_alloc();
+            icon.set_name("Icon");
+            icon.set_text(kind.icon());
+            icon.set_horizontal_alignment(godot::global::HorizontalAlignment::CENTER);
+            icon.add_theme_color_override("font_color", pickup::kind_color(kind, false));
+
+            let mut countdown = ProgressBar::new_alloc();
+            countdown.set_name("Countdown");
+            countdown.set_max(1.0);
+            countdown.set_step(0.0);
+            countdown.set_show_percentage(false);
+            countdown.set_custom_minimum_size(Vector2::new(48.0, 6.0));
+
+            let mut entry = VBoxContainer::new_alloc();
+            entry.set_name(Self::power_up_node_name(kind).as_str());
+            entry.add_child(&icon);
+            entry.add_child(&countdown);
+            entry.hide();
+            power_up_bar.add_child(&entry);
+        }
+
+        // Show the title screen buttons again when the settings menu is closed,
+        // and go between the settings and the controls menus.
+        let hud = self.to_gd();
+        let settings_menu = self.settings_menu();
+        settings_menu
+            .signals()
+            .closed()
+            .connect_obj(&hud, Self::on_settings_menu_closed);
+        settings_menu
+            .signals()
+            .controls_requested()
+            .connect_obj(&hud, Self::on_controls_requested);
+        self.base()
+            .get_node_as::<ControlsMenu>("ControlsMenu")
+            .signals()
+            .closed()
+            .connect_obj(&hud, Self::on_controls_menu_closed);
+
+        // The settings can be changed from the pause menu too.
+        self.pause_menu()
+            .signals()
+            .settings_requested()
+            .connect_obj(&hud, Self::on_pause_settings_requested);
+    }
 }
 
 #[godot_api]
@@ -30,6 +104,9 @@ impl HUD {
     #[signal]
     pub fn start_game();
 
+    #[signal]
+    pub fn name_entered(name: GString);
+
     #[func]
     /// This function shows a message on the screen.
     ///
@@ -58,10 +135,18 @@ impl HUD {
         timer.start();
     }
 
-    pub fn show_game_over(&mut self) {
+    pub fn show_game_over(&mut self, seed: i64) {
         // Show the "Done! ;)" message after the game is over.
         self.show_message("Done! ;)".into());
 
+        // There is nothing left to pause.
+        self.base().get_node_as::<Button>("PauseButton").hide();
+
+        // Show the seed of the run so it can be replayed exactly.
+        let mut seed_label = self.base().get_node_as::<Label>("SeedLabel");
+        seed_label.set_text(&format!("Seed: {seed}"));
+        seed_label.show();
+
         // Create a new Timer node with a duration of 2 seconds.
         let mut timer = self
             .base()
@@ -77,6 +162,66 @@ impl HUD {
 
     }
 
+    /// This function shows the high score table below the score.
+    ///
+    /// Each entry is shown on its own line as rank, name and score.
+    /// The entry at `highlight` (the record that was just set, if any) is marked with arrows.
+    pub fn show_high_scores(&mut self, table: &HighScoreTable, highlight: Option<usize>) {
+        // Build one line per entry, e.g. "1. Player  42".
+        let lines: Vec<String> = table
+            .entries()
+            .iter()
+            .enumerate()
+            .map(|(rank, entry)| {
+                let line = format!("{}. {}  {}", rank + 1, entry.name, entry.score);
+                if Some(rank) == highlight {
+                    format!("> {line} <")
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        // Get a reference to the "HighScoreLabel" node.
+        let mut high_score_label = self.base().get_node_as::<Label>("HighScoreLabel");
+
+        // Show the table, or a hint if nobody has scored yet.
+        if lines.is_empty() {
+            high_score_label.set_text("No high scores yet");
+        } else {
+            high_score_label.set_text(&lines.join("\n"));
+        }
+        high_score_label.show();
+    }
+
+    /// This function asks the player to type a name for a new record.
+    ///
+    /// The "NameEntry" field is pre-filled with `name` and focused.
+    /// Submitting it emits the `name_entered` signal.
+    pub fn prompt_name(&mut self, name: &str) {
+        // Get a reference to the "NameEntry" node.
+        let mut name_entry = self.base().get_node_as::<LineEdit>("NameEntry");
+
+        // Pre-fill the field and select the text so typing replaces it.
+        name_entry.set_max_length(MAX_NAME_LENGTH as i32);
+        name_entry.set_text(name);
+        name_entry.show();
+        name_entry.grab_focus();
+        name_entry.select_all();
+    }
+
+    #[func]
+    /// This function is called when the player presses enter in the "NameEntry" field.
+    /// It hides the field and passes the name on with the `name_entered` signal.
+    pub fn on_name_entry_text_submitted(&mut self, text: GString) {
+        // Hide the name entry field now that the name has been entered.
+        self.base().get_node_as::<LineEdit>("NameEntry").hide();
+
+        // Emit a signal with the name.
+        // This signal is used by the Game node to store the name with the record.
+        self.signals().name_entered().emit(&text);
+    }
+
     #[func]
     /// This function shows the start button and a message on the screen.
     /// The message is "Fugitive!!" and is displayed in the "MessageLabel" node.
@@ -96,6 +241,21 @@ impl HUD {
 
         // Show the start button.
         start_button.show();
+
+        // Show the difficulty selector so the next run can be played on another preset.
+        // It takes the place of the lives counter.
+        self.base().get_node_as::<Label>("LivesLabel").hide();
+        self.base().get_node_as::<ProgressBar>("DashCooldown").hide();
+        self.base().get_node_as::<Label>("ComboLabel").hide();
+        self.base().get_node_as::<OptionButton>("DifficultyButton").show();
+        self.base().get_node_as::<Button>("SettingsButton").show();
+        self.base().get_node_as::<Button>("PauseButton").hide();
+    }
+
+    /// This function returns the difficulty preset picked in the "DifficultyButton" selector.
+    pub fn selected_difficulty(&self) -> DifficultyPreset {
+        let difficulty_button = self.base().get_node_as::<OptionButton>("DifficultyButton");
+        DifficultyPreset::from_index(difficulty_button.get_selected() as i64)
     }
 
     #[func]
@@ -112,6 +272,166 @@ impl HUD {
             
     }
 
+    /// This function shows an icon with a countdown bar for every running power-up
+    /// and hides the others.
+    pub fn update_power_ups(&mut self, power_ups: &PowerUps) {
+        let power_up_bar = self.base().get_node_as::<HBoxContainer>("PowerUpBar");
+        for kind in PowerUpKind::ALL {
+            let Some(mut entry) = power_up_bar
+                .get_node_or_null(Self::power_up_node_name(kind).as_str())
+                .and_then(|node| node.try_cast::<VBoxContainer>().ok())
+            else {
+                continue;
+            };
+
+            match power_ups.get(kind) {
+                Some(active) => {
+                    entry
+                        .get_node_as::<ProgressBar>("Countdown")
+                        .set_value(f64::from(active.fraction()));
+                    entry.show();
+                }
+                None => entry.hide(),
+            }
+        }
+    }
+
+    fn power_up_node_name(kind: PowerUpKind) -> String {
+        format!("PowerUp{}", kind.index())
+    }
+
+    /// This function shows the number of live mobs and the mob pool statistics
+    /// in the "MobCountLabel" node, for debugging. Passing `None` hides the label.
+    pub fn show_mob_count(&mut self, stats: Option<(usize, PoolStats)>) {
+        let mut mob_count_label = self.base().get_node_as::<Label>("MobCountLabel");
+        match stats {
+            Some((count, pool)) => {
+                mob_count_label.set_text(&format!("Mobs: {count}\nPool: {pool}"));
+                mob_count_label.show();
+            }
+            None => mob_count_label.hide(),
+        }
+    }
+
+    /// This function shows how many lives the player has left in the "LivesLabel" node.
+    pub fn update_lives(&mut self, lives: i32) {
+        let mut lives_label = self.base().get_node_as::<Label>("LivesLabel");
+        lives_label.set_text(&format!("Lives: {lives}"));
+        lives_label.show();
+    }
+
+    /// This function shows the graze combo and its multiplier in the "ComboLabel" node,
+    /// and hides the label while there is no combo.
+    pub fn update_combo(&mut self, combo: &GrazeCombo) {
+        let mut combo_label = self.base().get_node_as::<Label>("ComboLabel");
+        if combo.is_active() {
+            combo_label.set_text(&format!(
+                "Graze {}  x{:.1}",
+                combo.count(),
+                combo.multiplier()
+            ));
+            combo_label.show();
+        } else {
+            combo_label.hide();
+        }
+    }
+
+    /// This function fills the "DashCooldown" bar with how far the dash has recharged,
+    /// from 0 (just dashed) to 1, and dims it until the dash is ready again.
+    pub fn update_dash_cooldown(&mut self, readiness: f32) {
+        let mut dash_cooldown = self.base().get_node_as::<ProgressBar>("DashCooldown");
+        dash_cooldown.set_value(f64::from(readiness));
+        let alpha = if readiness >= 1.0 { 1.0 } else { 0.5 };
+        dash_cooldown.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, alpha));
+        dash_cooldown.show();
+    }
+
+    /// This function recolours the power-up icons, from the colourblind palette if `colorblind` is set.
+    pub fn set_colorblind_palette(&mut self, colorblind: bool) {
+        let power_up_bar = self.base().get_node_as::<HBoxContainer>("PowerUpBar");
+        for kind in PowerUpKind::ALL {
+            let Some(mut icon) = power_up_bar
+                .get_node_or_null(&format!("{}/Icon", Self::power_up_node_name(kind)))
+                .and_then(|node| node.try_cast::<Label>().ok())
+            else {
+                continue;
+            };
+            icon.add_theme_color_override("font_color", pickup::kind_color(kind, colorblind));
+        }
+    }
+
+    /// This function shows the on-screen joystick on touch screens, or hides it while
+    /// the player follows the touch point instead.
+    pub fn set_joystick_enabled(&mut self, enabled: bool) {
+        self.base()
+            .get_node_as::<VirtualJoystick>("VirtualJoystick")
+            .bind_mut()
+            .set_enabled(enabled);
+    }
+
+    /// This function returns the "SettingsMenu" node.
+    pub fn settings_menu(&self) -> Gd<SettingsMenu> {
+        self.base().get_node_as::<SettingsMenu>("SettingsMenu")
+    }
+
+    #[func]
+    /// This function is called when the settings button is pressed.
+    /// It opens the settings menu in place of the title screen buttons.
+    pub fn on_settings_button_pressed(&mut self) {
+        self.base().get_node_as::<Button>("StartButton").hide();
+        self.base().get_node_as::<OptionButton>("DifficultyButton").hide();
+        self.base().get_node_as::<Button>("SettingsButton").hide();
+        self.settings_menu().bind_mut().open();
+    }
+
+    /// This function returns the "PauseMenu" node.
+    pub fn pause_menu(&self) -> Gd<PauseMenu> {
+        self.base().get_node_as::<PauseMenu>("PauseMenu")
+    }
+
+    /// This function shows the pause menu while the game is paused and hides it otherwise,
+    /// along with the settings and controls menus that can be opened from it.
+    pub fn show_pause_menu(&mut self, paused: bool) {
+        self.pause_menu().set_visible(paused);
+        if !paused {
+            self.settings_menu().hide();
+            self.base().get_node_as::<ControlsMenu>("ControlsMenu").hide();
+        }
+    }
+
+    /// This function is called when the "Settings" button of the pause menu is pressed.
+    fn on_pause_settings_requested(&mut self) {
+        self.settings_menu().bind_mut().open();
+    }
+
+    /// This function is called when the settings menu is closed. It goes back to the pause menu
+    /// if the game is paused, or brings the title screen buttons back.
+    fn on_settings_menu_closed(&mut self) {
+        let paused = self.base().get_tree().is_some_and(|tree| tree.is_paused());
+        if paused {
+            self.pause_menu().show();
+            return;
+        }
+
+        self.base().get_node_as::<Button>("StartButton").show();
+        self.base().get_node_as::<OptionButton>("DifficultyButton").show();
+        self.base().get_node_as::<Button>("SettingsButton").show();
+    }
+
+    /// This function is called when the "Controls" button of the settings menu is pressed.
+    /// It opens the controls menu in place of the settings menu.
+    fn on_controls_requested(&mut self) {
+        self.base()
+            .get_node_as::<ControlsMenu>("ControlsMenu")
+            .bind_mut()
+            .open();
+    }
+
+    /// This function is called when the controls menu is closed, and goes back to the settings menu.
+    fn on_controls_menu_closed(&mut self) {
+        self.settings_menu().bind_mut().open();
+    }
+
     #[func]
     /// This function is called when the start button is pressed.
     /// It is responsible for hiding the start button, emitting a signal to start the game, and playing a sound effect.
@@ -122,6 +442,19 @@ impl HUD {
         // Hide the start button so that it is no longer visible on the screen.
         start_button.hide();
 
+        // Hide the difficulty selector and the settings button while the game is running,
+        // and show the pause button instead, for touch screens without a pause key.
+        self.base().get_node_as::<OptionButton>("DifficultyButton").hide();
+        self.base().get_node_as::<Button>("SettingsButton").hide();
+        self.base().get_node_as::<Button>("PauseButton").show();
+
+        // Hide the seed and the high scores of the previous run.
+        self.base().get_node_as::<Label>("SeedLabel").hide();
+        self.base().get_node_as::<Label>("HighScoreLabel").hide();
+
+        // Stop waiting for a name; the record keeps the name it was saved with.
+        self.base().get_node_as::<LineEdit>("NameEntry").hide();
+
         // Emit a signal to start the game.
         // This signal is used by the Game node to start the game.
         self.signals().start_game().emit();
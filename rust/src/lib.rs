@@ -2,8 +2,20 @@ use godot::prelude::*;
 
 mod player;
 mod mob;
+mod mob_pool;
+mod pickup;
 mod game;
 mod hud;
+mod audio;
+mod bindings;
+mod controls_menu;
+mod pause_menu;
+mod settings;
+mod settings_menu;
+mod bridge;
+mod storage;
+mod virtual_joystick;
+pub mod sim;
 
 struct RustScriptExtension;
 
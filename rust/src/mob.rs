@@ -1,6 +1,15 @@
-use godot::classes::{AnimatedSprite2D, IRigidBody2D, RigidBody2D};
+use std::collections::HashMap;
+
+use crate::sim::archetype::{MobArchetype, MovementPattern};
+use crate::sim::arena::{self, Arena};
+use crate::sim::behaviour::{self, BehaviourContext, MobBehaviour, MobState};
+
+use godot::classes::{AnimatedSprite2D, CapsuleShape2D, CollisionShape2D, IRigidBody2D, Node2D, RigidBody2D};
 use godot::prelude::*;
 
+/// Every mob in the scene tree is in this group, so they can be counted and cleared together.
+pub const GROUP: &str = "mobs";
+
 
 #[derive(GodotClass)]
 #[class(base=RigidBody2D)]
@@ -9,6 +18,24 @@ pub struct Mob {
     pub min_speed: real,
     pub max_speed: real,
 
+    // How the mob moves, set from its archetype.
+    pub movement: MovementPattern,
+    // Name of the archetype, so the halves of a split mob can be spawned as the same type.
+    archetype: GString,
+    // Steers the mob every physics frame; `None` keeps the spawn velocity.
+    behaviour: Option<Box<dyn MobBehaviour>>,
+    // Whether the mob is in play, as opposed to waiting to go back to the pool.
+    active: bool,
+
+    // The sprite scale and capsule height from `mob.tscn`, which archetypes are scaled from.
+    base_sprite_scale: Vector2,
+    base_capsule_height: real,
+    // The capsule made for each archetype this mob has been, reused when it respawns as one again.
+    capsules: HashMap<String, Gd<CapsuleShape2D>>,
+
+    // The play area the mob bounces in and is despawned outside of, set by the game.
+    arena: Arena,
+
     base: Base<RigidBody2D>
 }
 
@@ -21,6 +48,7 @@ impl IRigidBody2D for Mob {
     /// It returns a new instance of `Mob` with the following properties:
     /// - `min_speed`: The minimum speed of the mob, in units of the physics engine.
     /// - `max_speed`: The maximum speed of the mob, in units of the physics engine.
+    /// - `movement`: How the mob moves after it has been spawned.
     /// - `base`: The base node of the mob, which is a `RigidBody2D`.
     ///
     /// The default values of `min_speed` and `max_speed` are 300.0 and 600.0 respectively.
@@ -29,6 +57,14 @@ impl IRigidBody2D for Mob {
         Mob {
             min_speed: 300.0,
             max_speed: 600.0,
+            movement: MovementPattern::Straight,
+            archetype: GString::new(),
+            behaviour: None,
+            active: false,
+            base_sprite_scale: Vector2::ONE,
+            base_capsule_height: 0.0,
+            capsules: HashMap::new(),
+            arena: Arena::default(),
             base
         }
     }
@@ -59,7 +95,205 @@ impl IRigidBody2D for Mob {
         // Set the animation to 'fly'. This is a placeholder until the random
         // animation code is fixed.
         sprite.set_animation("fly");
-        
+
+        // Join the mob group even if the scene was saved without it.
+        self.base_mut().add_to_group(GROUP);
+
+        // Remember the sizes from the scene so archetypes can scale them.
+        self.base_sprite_scale = sprite.get_scale();
+        if let Some(capsule) = self.capsule_shape() {
+            self.base_capsule_height = capsule.get_height();
+        }
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        if self.behaviour.is_none() {
+            return;
+        }
+
+        let state = MobState {
+            position: self.base().get_global_position().into(),
+            velocity: self.base().get_linear_velocity().into(),
+        };
+
+        // A mob that has flown far enough past the arena edge will never come back.
+        if !self.arena.contains(state.position, arena::DESPAWN_MARGIN) {
+            self.despawn();
+            return;
+        }
+
+        // Behaviours only chase the player while they are alive (visible).
+        let player = self
+            .base()
+            .get_tree()
+            .and_then(|tree| tree.get_first_node_in_group("player"))
+            .and_then(|node| node.try_cast::<Node2D>().ok())
+            .filter(|player| player.is_visible())
+            .map(|player| player.get_global_position().into());
+        let context = BehaviourContext {
+            player,
+            arena: self.arena.size,
+        };
+
+        let delta = delta as f32;
+        let Some(steering) = self
+            .behaviour
+            .as_mut()
+            .map(|behaviour| behaviour.steer(&state, &context, delta))
+        else {
+            return;
+        };
+        self.base_mut().set_linear_velocity(steering.velocity.into());
+
+        // Turn the sprite to face the new heading over this frame.
+        if delta > 0.0 {
+            let turn = behaviour::angle_difference(
+                self.base().get_rotation().as_f32(),
+                steering.velocity.angle(),
+            );
+            self.base_mut().set_angular_velocity(real::from_f32(turn / delta));
+        }
+
+        // Ask the game to spawn the other half of a split mob.
+        if let Some(velocity) = steering.split {
+            let archetype = self.archetype.clone();
+            self.signals().split().emit(
+                self.base().get_global_position(),
+                velocity.into(),
+                &archetype,
+            );
+        }
+    }
+}
+
+#[godot_api]
+impl Mob {
+    /// Emitted when a splitting mob bounces off the screen edge, with the
+    /// position, velocity and archetype name of the second half.
+    #[signal]
+    pub fn split(position: Vector2, velocity: Vector2, archetype: GString);
+
+    /// Emitted when the mob has left the arena and can go back to the pool.
+    #[signal]
+    pub fn despawned(mob: Gd<Mob>);
+
+    /// This function puts a mob taken from the pool back into play.
+    /// It must be called after the mob has been added to the scene tree.
+    pub fn activate(&mut self) {
+        self.active = true;
+        self.base_mut().show();
+
+        // Restart the animation from its first frame.
+        let mut sprite = self
+            .base()
+            .get_node_as::<AnimatedSprite2D>("AnimatedSprite2D");
+        sprite.set_frame_and_progress(0, 0.0);
+        sprite.play();
+    }
+
+    /// This function takes the mob out of play until it is back in the pool.
+    /// It stops the mob and hides it; removing it from the scene tree is up to the pool's owner.
+    pub fn deactivate(&mut self) {
+        self.active = false;
+        self.behaviour = None;
+        self.movement = MovementPattern::Straight;
+
+        let mut base = self.base_mut();
+        base.set_linear_velocity(Vector2::ZERO);
+        base.set_angular_velocity(0.0);
+        base.hide();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// This function turns the mob into the given archetype.
+    ///
+    /// It sets the speed range and movement pattern, scales the sprite,
+    /// resizes the collision capsule and plays the archetype's animation.
+    /// It must be called after the mob has been added to the scene tree.
+    pub fn apply_archetype(&mut self, archetype: &MobArchetype) {
+        self.min_speed = real::from_f32(archetype.min_speed);
+        self.max_speed = real::from_f32(archetype.max_speed);
+        self.movement = archetype.movement;
+        self.archetype = archetype.name.as_str().into();
+        self.behaviour = Some(archetype.movement.behaviour());
+
+        let scale = real::from_f32(archetype.scale);
+
+        // Scale the sprite relative to its size in the scene.
+        let mut sprite = self
+            .base()
+            .get_node_as::<AnimatedSprite2D>("AnimatedSprite2D");
+        sprite.set_scale(self.base_sprite_scale * scale);
+
+        // Play the archetype's animation, if the sprite has it.
+        let has_animation = sprite
+            .get_sprite_frames()
+            .is_some_and(|frames| frames.has_animation(archetype.animation.as_str()));
+        if has_animation {
+            sprite.play_ex().name(archetype.animation.as_str()).done();
+        } else {
+            godot_warn!(
+                "Mob type '{}' uses missing animation '{}'",
+                archetype.name,
+                archetype.animation
+            );
+        }
+
+        // Give this mob its own capsule for the archetype so resizing it does not affect other mobs.
+        // It is made the first time the mob becomes this archetype and reused when it respawns as it,
+        // so pooled mobs do not allocate a shape on every spawn.
+        let capsule = match self.capsules.get(&archetype.name) {
+            Some(capsule) => Some(capsule.clone()),
+            None => self.make_capsule(archetype, scale),
+        };
+        if let Some(capsule) = capsule {
+            self.base()
+                .get_node_as::<CollisionShape2D>("CollisionShape2D")
+                .set_shape(&capsule);
+        }
+    }
+
+    /// This function makes a copy of the capsule in `mob.tscn` sized for the archetype and caches it.
+    /// The capsule's height has to stay at least twice its radius.
+    fn make_capsule(&mut self, archetype: &MobArchetype, scale: real) -> Option<Gd<CapsuleShape2D>> {
+        let mut capsule = self
+            .capsule_shape()?
+            .duplicate()?
+            .cast::<CapsuleShape2D>();
+        let radius = real::from_f32(archetype.collision_radius) * scale;
+        capsule.set_radius(radius);
+        capsule.set_height((self.base_capsule_height * scale).max(radius * 2.0));
+        self.capsules.insert(archetype.name.clone(), capsule.clone());
+        Some(capsule)
+    }
+
+    /// This function sets the play area of the mob. It is called on spawn and whenever the arena changes.
+    pub fn set_arena(&mut self, arena: Arena) {
+        self.arena = arena;
+    }
+
+    /// This function is called when the mob has left the arena.
+    /// The mob will never come back, so it is stopped and handed back with the `despawned` signal.
+    fn despawn(&mut self) {
+        // Only despawn once.
+        if !self.active {
+            return;
+        }
+
+        self.deactivate();
+        let mob = self.to_gd();
+        self.signals().despawned().emit(&mob);
+    }
+
+    fn capsule_shape(&self) -> Option<Gd<CapsuleShape2D>> {
+        self.base()
+            .get_node_as::<CollisionShape2D>("CollisionShape2D")
+            .get_shape()?
+            .try_cast::<CapsuleShape2D>()
+            .ok()
     }
 }
 
@@ -0,0 +1,79 @@
+use crate::mob::Mob;
+
+use godot::prelude::*;
+
+use std::fmt;
+
+/// How often the pool could hand out a recycled mob.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Mobs handed out from the pool.
+    pub hits: u32,
+    /// Mobs that had to be instantiated because the pool was empty.
+    pub misses: u32,
+}
+
+impl fmt::Display for PoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hits / {} misses", self.hits, self.misses)
+    }
+}
+
+/// Mob instances that are not in the scene tree, ready to be spawned again.
+///
+/// Instantiating `mob.tscn` is expensive (especially on the web build), so
+/// mobs that leave the screen or are cleared are handed back here instead of
+/// being freed.
+#[derive(Default)]
+pub struct MobPool {
+    idle: Vec<Gd<Mob>>,
+    stats: PoolStats,
+}
+
+impl MobPool {
+    /// Instantiates mobs until `count` of them are idle.
+    ///
+    /// Each new mob is passed to `on_created` so its signals can be connected once.
+    pub fn prewarm(&mut self, scene: &Gd<PackedScene>, count: usize, mut on_created: impl FnMut(&mut Gd<Mob>)) {
+        while self.idle.len() < count {
+            let mut mob = scene.instantiate_as::<Mob>();
+            on_created(&mut mob);
+            self.idle.push(mob);
+        }
+    }
+
+    /// Takes an idle mob, or instantiates a new one (passed to `on_created`) if there is none.
+    pub fn acquire(&mut self, scene: &Gd<PackedScene>, on_created: impl FnOnce(&mut Gd<Mob>)) -> Gd<Mob> {
+        if let Some(mob) = self.idle.pop() {
+            self.stats.hits += 1;
+            return mob;
+        }
+
+        self.stats.misses += 1;
+        let mut mob = scene.instantiate_as::<Mob>();
+        on_created(&mut mob);
+        mob
+    }
+
+    /// Hands a mob back once it has been removed from the scene tree.
+    pub fn release(&mut self, mob: Gd<Mob>) {
+        self.idle.push(mob);
+    }
+
+    pub fn idle_count(&self) -> usize {
+        self.idle.len()
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+}
+
+impl Drop for MobPool {
+    fn drop(&mut self) {
+        // Idle mobs are outside the scene tree, so nothing else would free them.
+        for mob in self.idle.drain(..) {
+            mob.free();
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::sim::bindings::Action;
+
+use godot::classes::{Button, IPanelContainer, InputEvent, Label, PanelContainer, VBoxContainer};
+use godot::prelude::*;
+
+
+/// The overlay shown while the game is paused.
+///
+/// It keeps processing while the scene tree is paused (see `process_mode` in `hud.tscn`),
+/// so the pause action can close it again. The game decides whether a pause is possible
+/// and does the actual pausing, in answer to the signals.
+#[derive(GodotClass)]
+#[class(base=PanelContainer)]
+pub struct PauseMenu {
+    base: Base<PanelContainer>,
+}
+
+#[godot_api]
+impl IPanelContainer for PauseMenu {
+    // This function initializes a new PauseMenu instance.
+    fn init(base: Base<PanelContainer>) -> Self {
+        Self { base }
+    }
+
+    fn ready(&mut self) {
+        // Build the menu: a title and a button for every choice, stacked in a column.
+        let mut rows = VBoxContainer::new_alloc();
+        rows.add_theme_constant_override("separation", 12);
+
+        let mut title = Label::new_alloc();
+        title.set_text("Paused");
+        title.set_horizontal_alignment(godot::global::HorizontalAlignment::CENTER);
+        rows.add_child(&title);
+
+        for (text, method) in [
+            ("Resume", "on_resume_pressed"),
+            ("Restart", "on_restart_pressed"),
+            ("Settings", "on_settings_pressed"),
+            ("Quit to title", "on_quit_pressed"),
+        ] {
+            let mut button = Button::new_alloc();
+            button.set_text(text);
+            button.connect("pressed", &self.base().callable(method));
+            rows.add_child(&button);
+        }
+
+        self.base_mut().add_child(&rows);
+    }
+
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if !event.is_action_pressed(Action::Pause.name()) {
+            return;
+        }
+
+        // The pause action closes the menu while it is open and asks for a pause otherwise.
+        if self.base().is_visible() {
+            self.on_resume_pressed();
+        } else {
+            self.request_pause();
+        }
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.set_input_as_handled();
+        }
+    }
+}
+
+#[godot_api]
+impl PauseMenu {
+    /// Emitted when the player asks to pause, with the pause action or the HUD's pause button.
+    #[signal]
+    pub fn pause_requested();
+
+    /// Emitted when the player chooses to carry on.
+    #[signal]
+    pub fn resumed();
+
+    #[signal]
+    pub fn restart_requested();
+
+    /// Emitted when the player asks for the settings menu. The pause menu hides itself first.
+    #[signal]
+    pub fn settings_requested();
+
+    /// Emitted when the player gives up the run to go back to the title screen.
+    #[signal]
+    pub fn quit_requested();
+
+    #[func]
+    /// This function asks the game to pause. It is also called by the "PauseButton" on the HUD.
+    pub fn request_pause(&mut self) {
+        self.signals().pause_requested().emit();
+    }
+
+    #[func]
+    fn on_resume_pressed(&mut self) {
+        self.signals().resumed().emit();
+    }
+
+    #[func]
+    fn on_restart_pressed(&mut self) {
+        self.signals().restart_requested().emit();
+    }
+
+    #[func]
+    fn on_settings_pressed(&mut self) {
+        self.base_mut().hide();
+        self.signals().settings_requested().emit();
+    }
+
+    #[func]
+    fn on_quit_pressed(&mut self) {
+        self.signals().quit_requested().emit();
+    }
+}
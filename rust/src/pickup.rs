@@ -0,0 +1,112 @@
+use crate::player::Player;
+use crate::sim::powerup::PowerUpKind;
+
+use godot::classes::{Area2D, IArea2D, Label, Sprite2D};
+use godot::prelude::*;
+
+/// Every pickup waiting to be collected is in this group, so they can be cleared together.
+pub const GROUP: &str = "pickups";
+
+/// Seconds a pickup stays on screen before it disappears uncollected.
+const LIFETIME: f32 = 6.0;
+
+/// For how many of its last seconds a pickup blinks to warn that it is about to disappear.
+const BLINK_TIME: f32 = 2.0;
+
+
+#[derive(GodotClass)]
+#[class(base=Area2D)]
+pub struct Pickup {
+    kind: PowerUpKind,
+    // Seconds left before the pickup disappears.
+    lifetime: f32,
+
+    base: Base<Area2D>
+}
+
+#[godot_api]
+impl IArea2D for Pickup {
+    // This function initializes a new Pickup instance.
+    // The kind is set with `set_kind` before the pickup is added to the scene.
+    fn init(base: Base<Area2D>) -> Self {
+        Pickup {
+            kind: PowerUpKind::Shield,
+            lifetime: LIFETIME,
+            base
+        }
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        self.lifetime -= delta as f32;
+
+        if self.lifetime <= 0.0 {
+            self.base_mut().queue_free();
+            return;
+        }
+
+        // Blink four times a second while the pickup is about to disappear.
+        let visible = self.lifetime > BLINK_TIME || (self.lifetime * 8.0) as i32 % 2 == 0;
+        self.base_mut().set_visible(visible);
+    }
+}
+
+#[godot_api]
+impl Pickup {
+    /// Emitted when the player touches the pickup, with the `PowerUpKind` index.
+    #[signal]
+    pub fn collected(kind: i64);
+
+    /// This function sets which power-up the pickup gives and colours it to match,
+    /// from the colourblind palette if `colorblind` is set.
+    pub fn set_kind(&mut self, kind: PowerUpKind, colorblind: bool) {
+        self.kind = kind;
+
+        self.base()
+            .get_node_as::<Sprite2D>("Glow")
+            .set_modulate(kind_color(kind, colorblind));
+        self.base()
+            .get_node_as::<Label>("IconLabel")
+            .set_text(kind.icon());
+    }
+
+    #[func]
+    /// This function is called when another area enters the pickup.
+    /// If it is the player, the pickup is collected and removed.
+    fn on_area_entered(&mut self, area: Gd<Area2D>) {
+        // Only a living player can collect pickups.
+        let Ok(player) = area.try_cast::<Player>() else {
+            return;
+        };
+        if !player.bind().is_alive() || !player.is_visible() {
+            return;
+        }
+
+        let kind = self.kind.index() as i64;
+        self.signals().collected().emit(kind);
+        self.base_mut().queue_free();
+    }
+}
+
+/// The colour of a power-up's pickup and HUD icon.
+///
+/// The colourblind palette (Okabe and Ito's) keeps the five kinds apart for every
+/// common kind of colour blindness; the icons tell them apart too.
+pub fn kind_color(kind: PowerUpKind, colorblind: bool) -> Color {
+    if colorblind {
+        return match kind {
+            PowerUpKind::Shield => Color::from_rgb(0.34, 0.71, 0.91),
+            PowerUpKind::SlowMotion => Color::from_rgb(0.8, 0.47, 0.65),
+            PowerUpKind::Shrink => Color::from_rgb(0.0, 0.62, 0.45),
+            PowerUpKind::ScoreMultiplier => Color::from_rgb(0.94, 0.89, 0.26),
+            PowerUpKind::Bomb => Color::from_rgb(0.84, 0.37, 0.0),
+        };
+    }
+
+    match kind {
+        PowerUpKind::Shield => Color::from_rgb(0.4, 0.8, 1.0),
+        PowerUpKind::SlowMotion => Color::from_rgb(0.7, 0.5, 1.0),
+        PowerUpKind::Shrink => Color::from_rgb(0.5, 1.0, 0.5),
+        PowerUpKind::ScoreMultiplier => Color::from_rgb(1.0, 0.85, 0.3),
+        PowerUpKind::Bomb => Color::from_rgb(1.0, 0.4, 0.3),
+    }
+}
@@ -1,12 +1,78 @@
+use std::collections::HashSet;
 
-use godot::classes::{AnimatedSprite2D, Area2D, CollisionShape2D, IArea2D, PhysicsBody2D};
+use crate::mob::Mob;
+use crate::sim::arena::Arena;
+use crate::sim::bindings::Action;
+use crate::sim::dash::{Dash, DashConfig};
+use crate::sim::death::{self, DeathSequence};
+use crate::sim::lives::{self, HitOutcome, Lives};
+use crate::sim::player::{MoveInput, PlayerBody, Stick};
+use crate::sim::player_state::{PlayerEvent, PlayerState, PlayerStateMachine};
+use crate::sim::replay::ReplayCursor;
+
+use godot::classes::{AnimatedSprite2D, Area2D, AudioListener2D, CollisionShape2D, Engine, IArea2D, PhysicsBody2D};
 use godot::prelude::*;
 
+/// The player's speed in pixels per second.
+const SPEED: f32 = 400.0;
+
+/// Tint of the player while a shield is up.
+const SHIELD_TINT: Color = Color::from_rgb(0.5, 0.85, 1.0);
+
 #[derive(GodotClass)]
 #[class(base=Area2D)]
 pub struct Player {
-    speed: real,
-    screen_size: Vector2,
+    // The engine-independent movement rules (speed, clamping).
+    body: PlayerBody,
+    // What the player is doing and which way they face, which picks the animation.
+    state: PlayerStateMachine,
+    // When set, movement comes from this recording instead of the keyboard.
+    replay: Option<ReplayCursor>,
+    // The movement input of every physics frame since `start`.
+    recording: Vec<MoveInput>,
+    // Whether a run is in progress and frames should be recorded.
+    recording_active: bool,
+    // Whether the next hit is absorbed by a shield power-up.
+    shielded: bool,
+    // Lives left in the run and the invulnerability after losing one.
+    lives: Lives,
+
+    /// Lives the player starts every run with.
+    #[export]
+    starting_lives: i32,
+
+    // The alive → dying → dead sequence after the last life is lost.
+    death: DeathSequence,
+
+    /// Slows the game down while the player is dying.
+    #[export]
+    slow_motion_death: bool,
+
+    // The dash in progress and its cooldown, set up from the exported values in `start`.
+    dash: Dash,
+
+    /// Pixels covered by one dash.
+    #[export]
+    dash_distance: f32,
+
+    /// Seconds the player cannot be hit after starting a dash.
+    #[export]
+    dash_invulnerability: f32,
+
+    /// Seconds from one dash until the next one is possible.
+    #[export]
+    dash_cooldown: f32,
+
+    // Mobs inside the "GrazeArea" that have not touched the player (yet).
+    grazing: HashSet<InstanceId>,
+
+    // Whether the player follows the mouse cursor or touch point when no key or stick is pressed.
+    follow_pointer: bool,
+
+    /// How far a gamepad stick or the on-screen joystick has to be pushed, from 0 to 1,
+    /// before the player moves. Pushing it further moves the player faster.
+    #[export]
+    stick_deadzone: f32,
 
     base: Base<Area2D>
 }
@@ -18,19 +84,36 @@ impl IArea2D for Player {
     fn init(base: Base<Area2D>) -> Self {
         Player {
             // Sets the initial speed of the player to 400.0 units.
-            speed: 400.0,
-            // Initializes the screen size to a Vector2 with both dimensions set to 0.0.
-            // This will likely be updated later with the actual screen size.
-            screen_size: Vector2::new(0.0, 0.0),
+            // The bounds start at the design size and follow the arena with `set_arena`.
+            body: PlayerBody::new(SPEED, Arena::default().size),
+            state: PlayerStateMachine::default(),
+            // Reads the keyboard until a replay is handed over with `set_replay`.
+            replay: None,
+            recording: Vec::new(),
+            recording_active: false,
+            shielded: false,
+            lives: Lives::default(),
+            starting_lives: lives::DEFAULT_LIVES as i32,
+            death: DeathSequence::default(),
+            slow_motion_death: true,
+            dash: Dash::default(),
+            dash_distance: DashConfig::default().distance,
+            dash_invulnerability: DashConfig::default().invulnerability,
+            dash_cooldown: DashConfig::default().cooldown,
+            grazing: HashSet::new(),
+            stick_deadzone: 0.2,
+            follow_pointer: false,
             // Assigns the provided base node to the player's base field.
             base
         }
     }
 
     fn ready(&mut self) {
-        // Set the player's screen size to the size of the viewport rectangle.
-        // This is useful for ensuring the player remains within the screen boundaries.
-        self.screen_size = self.base().get_viewport_rect().size;
+        // Hear positional sounds from where the player is rather than from the middle of the screen,
+        // so mobs coming in from the left are heard on the left.
+        self.base()
+            .get_node_as::<AudioListener2D>("AudioListener2D")
+            .make_current();
 
         // Hide the player's base node initially.
         // This could be used to keep the player hidden until certain conditions are met.
@@ -41,116 +124,261 @@ impl IArea2D for Player {
         // Process the player's physics logic (movement, animation, etc.) here.
         // This function is called every frame.
 
+        // Once the last life is lost the input is ignored and only the death sequence runs.
+        if self.state.is_dead() {
+            self.process_death(delta);
+            return;
+        }
+
         // Get the player's animated sprite node.
         let mut animated_sprite = self.base().get_node_as::<AnimatedSprite2D>("AnimatedSprite2D");
 
-        // Get the game's input singleton.
-        let input = Input::singleton();
-
-        // Create a variable to store the player's velocity.
-        let mut velocity = Vector2::new(0.0, 0.0);
-
-        // Check which direction buttons are being pressed and update velocity accordingly.
-        if input.is_action_pressed("move_left") {
-            // If the left arrow key is being pressed, set the x component of velocity to -1.0.
-            velocity.x -= 1.0;
-        }
-        if input.is_action_pressed("move_right") {
-            // If the right arrow key is being pressed, set the x component of velocity to 1.0.
-            velocity.x += 1.0;
-        }
-        if input.is_action_pressed("move_up") {
-            // If the up arrow key is being pressed, set the y component of velocity to -1.0.
-            velocity.y -= 1.0;
-        }
-        if input.is_action_pressed("move_down") {
-            // If the down arrow key is being pressed, set the y component of velocity to 1.0.
-            velocity.y += 1.0;
-        }
-
-        // If the player is moving in some direction (i.e. velocity is not the origin), then:
-        if velocity.length() > 0.0 {
-            // Normalize the velocity vector (i.e. set its magnitude to 1.0) and multiply it by the player's speed.
-            velocity = velocity.normalized() * self.speed;
-
-            // Determine the correct animation to play based on the direction of the velocity.
-            let animation = match (velocity.x, velocity.y) {
-                // If the player is moving right, play the "run_right" animation.
-                (x, y) if x.abs() > y.abs() && x > 0.0 => "run_right",
-                // If the player is moving left, play the "run_left" animation.
-                (x, y) if x.abs() > y.abs() && x < 0.0 => "run_left",
-                // If the player is moving up, play the "run_up" animation.
-                (x, y) if y.abs() >= x.abs() && y > 0.0 => "run_up",
-                // If the player is moving down, play the "run_down" animation.
-                (x, y) if y.abs() >= x.abs() && y < 0.0 => "run_down",
-                _ => "run_down", // Default to "run_down" if none of the above conditions are true.
-            };
+        // Count the invulnerability after a lost life down, blinking the sprite meanwhile,
+        // and make the player hittable again once it is over.
+        if self.lives.step(delta as f32) {
+            self.set_collision_enabled(true);
+        }
+        animated_sprite.set_visible(self.lives.blink_visible());
 
-            // Play the chosen animation.
-            animated_sprite.play_ex().name(animation).done();
+        // Read the state of the four movement actions for this frame,
+        // either from the replay being played back or from the keyboard and gamepad.
+        let move_input = match &mut self.replay {
+            // Once the recording runs out the player simply stands still.
+            Some(replay) => replay.next_frame().unwrap_or_default(),
+            None => {
+                // Get the game's input singleton.
+                // The actions are read through the input map, which holds the player's own bindings.
+                let input = Input::singleton();
+
+                // Read the same actions as an analogue vector too. Keys give full strength,
+                // a stick or the on-screen joystick anything in between, past the deadzone.
+                let stick = input
+                    .get_vector_ex(
+                        Action::MoveLeft.name(),
+                        Action::MoveRight.name(),
+                        Action::MoveUp.name(),
+                        Action::MoveDown.name(),
+                    )
+                    .deadzone(self.stick_deadzone)
+                    .done();
+                let mut stick = Stick::from_vector(stick.into());
+
+                // In the pointer control mode, head for the cursor or touch point while
+                // nothing else is pressed, without trying to leave the screen.
+                // It is recorded as a stick, so replays do not need to know about the pointer.
+                if self.follow_pointer && stick.is_zero() {
+                    let position = self.base().get_global_position().into();
+                    let target = self.body.clamp(self.base().get_global_mouse_position().into());
+                    stick = Stick::toward(position, target, self.body.speed, delta as f32);
+                }
+
+                MoveInput {
+                    left: input.is_action_pressed(Action::MoveLeft.name()),
+                    right: input.is_action_pressed(Action::MoveRight.name()),
+                    up: input.is_action_pressed(Action::MoveUp.name()),
+                    down: input.is_action_pressed(Action::MoveDown.name()),
+                    dash: input.is_action_just_pressed(Action::Dash.name()),
+                    stick,
+                }
+            }
+        };
+
+        // Record the input so the run can be saved as a replay.
+        // Every frame is recorded, even those where the state ignores it, so replays stay in step.
+        if self.recording_active {
+            self.recording.push(move_input);
+        }
+
+        // Ignore the input while staggering after a hit.
+        self.state.step(delta as f32);
+        let move_input = if self.state.accepts_input() {
+            move_input
         } else {
-            // If the player is not moving, play the corresponding idle animation.
-            let current_animation = animated_sprite.get_animation();
-            
-            // Convert the run animation to the corresponding idle animation.
-            let idle_animation = match current_animation {
-                // If the player is currently playing the "run_up" animation, play the "idle_up" animation instead.
-                anim if anim.contains("run_up") => "idle_up",
-                // If the player is currently playing the "run_down" animation, play the "idle_down" animation instead.
-                anim if anim.contains("run_down") => "idle_down",
-                // If the player is currently playing the "run_left" animation, play the "idle_left" animation instead.
-                anim if anim.contains("run_left") => "idle_left",
-                // If the player is currently playing the "run_right" animation, play the "idle_right" animation instead.
-                anim if anim.contains("run_right") => "idle_right",
-                // Default to "idle_down" if none of the above conditions are true.
-                _ => "idle_down",
-            };
+            MoveInput::default()
+        };
 
-            // Play the chosen idle animation.
-            animated_sprite.play_ex().name(idle_animation).done();
+        // Count the dash and its cooldown down, then start a new dash if one was asked for.
+        // Without a direction held, the player dashes the way they face.
+        if self.dash.step(delta as f32) {
+            self.state.handle(PlayerEvent::DashEnded);
+        }
+        if move_input.dash {
+            let direction = match move_input.movement() {
+                direction if direction.length_squared() > 0.0 => direction,
+                _ => self.state.facing().vector(),
+            };
+            if self.dash.try_start(direction) {
+                self.state.handle(PlayerEvent::DashStarted);
+            }
         }
 
-        // Update the player's position by adding the velocity * delta to the current position.
-        // Clamp the position to the edge of the screen to prevent the player from moving off the edge.
-        let change = velocity * real::from_f64(delta);
-        let position = self.base().get_global_position() + change;
-        let position = Vector2::new(
-            position.x.clamp(0.0, self.screen_size.x),
-            position.y.clamp(0.0, self.screen_size.y),
-        );
-        self.base_mut().set_global_position(position);
+        // Let the simulation move the player from its current position.
+        // It scales the velocity by how far the stick is pushed, or moves at full speed
+        // for the keys, and clamps the position to the edge of the screen.
+        // While dashing, the player moves with the dash instead of the input.
+        self.body.position = self.base().get_global_position().into();
+        let position = if self.state.state() == PlayerState::Dash {
+            self.body.move_with(self.dash.velocity(), delta as f32)
+        } else {
+            self.body.step(move_input, delta as f32)
+        };
+
+        // Let the state machine pick the animation from the movement and the direction the player is facing.
+        self.state.handle(PlayerEvent::Moved(self.body.velocity));
+        animated_sprite.play_ex().name(self.state.animation()).done();
+
+        // Move the node to the clamped position.
+        self.base_mut().set_global_position(position.into());
     }
 }
 
 
 #[godot_api]
 impl Player {
+    /// Emitted when the player loses their last life, as the death sequence starts.
     #[signal]
     pub fn hit();
 
+    /// Emitted when the death sequence is over and the player has disappeared.
+    #[signal]
+    pub fn death_finished();
+
+    /// Emitted when the player loses a life but has `remaining` lives left.
+    #[signal]
+    pub fn life_lost(remaining: i32);
+
+    /// Emitted when the shield absorbs a hit and goes down.
+    #[signal]
+    pub fn shield_broken();
+
+    /// Emitted when a mob touches the player while the dash makes them invulnerable.
+    #[signal]
+    pub fn dash_near_miss();
+
+    /// Emitted when a mob passes through the "GrazeArea" around the player without touching them.
+    #[signal]
+    pub fn grazed();
+
     #[func]
-    fn on_player_body_entered(&mut self, _body: Gd<PhysicsBody2D>) {
+    fn on_player_body_entered(&mut self, body: Gd<PhysicsBody2D>) {
         // This function is a callback that is called whenever a PhysicsBody2D enters the player's area.
         // The body parameter is the PhysicsBody2D that entered the player's area.
-        // The _ prefix on the parameter is a naming convention to indicate that the parameter is not used in the function body.
 
-        // Hide the player's node so that it is no longer visible.
-        // This is done to prevent the player from continuing to move after they have been hit.
-        self.base_mut().hide();
+        // A mob that touches the player does not count as a graze, whatever happens next.
+        self.grazing.remove(&body.instance_id());
 
-        // Emit a signal that the player has been hit.
-        // This signal is used by the Game node to track the player's score and to trigger the game over screen.
-        self.signals().hit().emit();
+        // Dashing through a mob is a near miss, not a hit.
+        if self.dash.is_invulnerable() {
+            self.signals().dash_near_miss().emit();
+            return;
+        }
+
+        // A shield absorbs the hit instead.
+        if self.shielded {
+            self.set_shielded(false);
+            self.signals().shield_broken().emit();
+            return;
+        }
+
+        match self.lives.hit() {
+            // Hits during the invulnerability window do nothing.
+            HitOutcome::Ignored => return,
+            HitOutcome::LifeLost { remaining } => {
+                // Stagger for a moment and ignore collisions until the invulnerability is over.
+                self.state.handle(PlayerEvent::Hurt);
+                self.set_collision_enabled(false);
+                self.signals().life_lost().emit(remaining as i32);
+                return;
+            }
+            // The last life is gone, so the run is over.
+            HitOutcome::OutOfLives => {}
+        }
 
-        // Get a reference to the player's CollisionShape2D node.
-        // The CollisionShape2D node is used to detect when other nodes enter the player's area.
-        let mut collision_shape = self
-            .base()
-            .get_node_as::<CollisionShape2D>("CollisionShape2D");
+        // The run is over, so stop recording input.
+        self.recording_active = false;
 
         // Disable the player's CollisionShape2D node.
         // This is done to prevent the player from continuing to detect other nodes after they have been hit.
-        collision_shape.set_deferred("disable", &true.to_variant());
+        self.set_collision_enabled(false);
+
+        // Emit a signal that the player has been hit.
+        // This signal is used by the Game node to end the run; the game over screen
+        // waits for the `death_finished` signal.
+        self.signals().hit().emit();
+
+        // Start dying. This comes after the `hit` signal, which resets the power-ups
+        // and with them the time scale.
+        self.state.handle(PlayerEvent::Died);
+        self.death.kill();
+        let mut animated_sprite = self.base().get_node_as::<AnimatedSprite2D>("AnimatedSprite2D");
+        animated_sprite.show();
+
+        // Play the "dead" animation if it has frames, otherwise freeze on the current frame.
+        let animation = self.state.animation();
+        let has_dead_frames = animated_sprite
+            .get_sprite_frames()
+            .is_some_and(|frames| frames.has_animation(animation) && frames.get_frame_count(animation) > 0);
+        if has_dead_frames {
+            animated_sprite.play_ex().name(animation).done();
+        } else {
+            animated_sprite.pause();
+        }
+
+        if self.slow_motion_death {
+            Engine::singleton().set_time_scale(f64::from(death::DEATH_TIME_SCALE));
+        }
+    }
+
+    #[func]
+    /// This function is called when a body enters the "GrazeArea" around the player.
+    /// Active mobs are remembered until they leave it again.
+    fn on_graze_body_entered(&mut self, body: Gd<PhysicsBody2D>) {
+        // Mobs passing by while the player cannot be hit, or is already dying, are no risk.
+        if !self.is_alive() || self.lives.is_invulnerable() {
+            return;
+        }
+        if body.clone().try_cast::<Mob>().is_ok_and(|mob| mob.bind().is_active()) {
+            self.grazing.insert(body.instance_id());
+        }
+    }
+
+    #[func]
+    /// This function is called when a body leaves the "GrazeArea" around the player.
+    /// A mob that got close without touching the player counts as a graze.
+    fn on_graze_body_exited(&mut self, body: Gd<PhysicsBody2D>) {
+        if !self.grazing.remove(&body.instance_id()) || !self.is_alive() {
+            return;
+        }
+        // Mobs cleared by a bomb or returned to the pool also leave the area, but they did not pass by.
+        if body.try_cast::<Mob>().is_ok_and(|mob| mob.bind().is_active()) {
+            self.signals().grazed().emit();
+        }
+    }
+
+    /// Runs one frame of the death sequence: fades the sprite out and,
+    /// at the end, hides the player and emits `death_finished`.
+    fn process_death(&mut self, delta: f64) {
+        // Count in real time, so the slow motion does not drag the sequence out.
+        let time_scale = Engine::singleton().get_time_scale().max(0.01);
+        let finished = self.death.step((delta / time_scale) as f32);
+
+        let alpha = 1.0 - self.death.progress();
+        self.base()
+            .get_node_as::<AnimatedSprite2D>("AnimatedSprite2D")
+            .set_modulate(Color::from_rgba(1.0, 1.0, 1.0, alpha));
+
+        if !finished {
+            return;
+        }
+
+        // Hide the player's node so that it is no longer visible.
+        self.base_mut().hide();
+
+        if self.slow_motion_death {
+            Engine::singleton().set_time_scale(1.0);
+        }
+
+        self.signals().death_finished().emit();
     }
 
     #[func]
@@ -159,19 +387,117 @@ impl Player {
         // This is done to position the player at the starting location.
         self.base_mut().set_global_position(position);
 
+        // Forget the mobs that were passing by at the end of the previous run.
+        self.grazing.clear();
+
+        // Start a fresh recording of the movement input for this run.
+        self.recording.clear();
+        self.recording_active = true;
+
+        // Start alive, with a full set of lives.
+        self.state.handle(PlayerEvent::Revived);
+        self.death.revive();
+        self.lives = Lives::new(self.starting_lives.max(1) as u32);
+
+        // Start with the dash ready, using the current tuning.
+        self.dash = Dash::new(DashConfig {
+            distance: self.dash_distance,
+            invulnerability: self.dash_invulnerability,
+            cooldown: self.dash_cooldown,
+            ..DashConfig::default()
+        });
+        let mut animated_sprite = self.base().get_node_as::<AnimatedSprite2D>("AnimatedSprite2D");
+        animated_sprite.set_modulate(Color::WHITE);
+        animated_sprite.show();
+
+        // Drop the power-ups of the previous run.
+        self.set_shielded(false);
+        self.set_size_scale(1.0);
+        self.set_speed_scale(1.0);
+
         // Show the player's node.
         // This is done to make the player visible again after they have been hit and their node has been hidden.
         self.base_mut().show();
 
-        // Get a reference to the player's CollisionShape2D node.
-        // The CollisionShape2D node is used to detect when other nodes enter the player's area.
-        let mut collision_shape = self
-            .base()
-            .get_node_as::<CollisionShape2D>("CollisionShape2D");
-
         // Enable the player's CollisionShape2D node.
         // This is done to allow the player to detect other nodes after they have been hit.
         // The player's CollisionShape2D node is disabled after they have been hit to prevent them from continuing to detect other nodes.
-        collision_shape.set_disabled(false);
+        self.set_collision_enabled(true);
+    }
+
+    /// This function keeps the player inside the given arena from now on.
+    /// The arena changes with the window size, so a player left outside it is moved back in.
+    pub fn set_arena(&mut self, arena: Arena) {
+        self.body.bounds = arena.size;
+        let position = self.body.clamp(self.base().get_global_position().into());
+        self.body.position = position;
+        self.base_mut().set_global_position(position.into());
+    }
+
+    /// This function ends the run without a death, e.g. when it is given up from the pause menu.
+    /// The player disappears and stops recording input.
+    pub fn stop(&mut self) {
+        self.recording_active = false;
+        self.set_collision_enabled(false);
+        self.base_mut().hide();
+    }
+
+    /// Whether the player is alive, as opposed to dying or dead.
+    pub fn is_alive(&self) -> bool {
+        !self.state.is_dead()
+    }
+
+    /// Lives the player has left in the current run.
+    pub fn lives(&self) -> i32 {
+        self.lives.remaining() as i32
+    }
+
+    /// How far the dash has recharged, from 0 (just dashed) to 1 (ready).
+    pub fn dash_readiness(&self) -> f32 {
+        self.dash.readiness()
+    }
+
+    /// Turns detection of mobs on or off.
+    ///
+    /// The change is deferred because collision shapes cannot change
+    /// while the physics engine is reporting collisions.
+    fn set_collision_enabled(&mut self, enabled: bool) {
+        self.base()
+            .get_node_as::<CollisionShape2D>("CollisionShape2D")
+            .set_deferred("disabled", &(!enabled).to_variant());
+    }
+
+    /// Raises or drops the shield that absorbs the next hit.
+    pub fn set_shielded(&mut self, shielded: bool) {
+        self.shielded = shielded;
+        let tint = if shielded { SHIELD_TINT } else { Color::WHITE };
+        self.base_mut().set_modulate(tint);
+    }
+
+    /// Scales the player's sprite and collision shape, e.g. for the shrink power-up.
+    pub fn set_size_scale(&mut self, scale: f32) {
+        self.base_mut().set_scale(Vector2::ONE * real::from_f32(scale));
+    }
+
+    /// Switches between moving with the movement actions only and also following the pointer.
+    pub fn set_follow_pointer(&mut self, follow_pointer: bool) {
+        self.follow_pointer = follow_pointer;
+    }
+
+    /// Scales the player's speed, e.g. to keep moving at full speed in slow motion.
+    pub fn set_speed_scale(&mut self, scale: f32) {
+        self.body.speed = SPEED * scale;
+    }
+
+    /// Drives the player from recorded frames instead of the keyboard.
+    /// Passing `None` hands control back to the keyboard.
+    pub fn set_replay(&mut self, frames: Option<Vec<MoveInput>>) {
+        self.replay = frames.map(ReplayCursor::new);
+    }
+
+    /// Takes the movement input recorded since the last `start`.
+    pub fn take_recording(&mut self) -> Vec<MoveInput> {
+        self.recording_active = false;
+        std::mem::take(&mut self.recording)
     }
 }
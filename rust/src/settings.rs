@@ -0,0 +1,71 @@
+use crate::sim::settings::{Settings, VolumeSetting};
+use crate::storage;
+
+use godot::classes::display_server::{VSyncMode, WindowMode};
+use godot::classes::{AudioServer, DisplayServer};
+use godot::global::linear_to_db;
+use godot::prelude::*;
+
+/// Where the player's settings are saved.
+pub const SETTINGS_FILE: &str = "user://settings.toml";
+
+/// Loads the saved settings, falling back to the defaults if there are none or the file is unusable.
+pub fn load() -> Settings {
+    let Some(bytes) = storage::read_bytes(SETTINGS_FILE) else {
+        return Settings::default();
+    };
+
+    let parsed = String::from_utf8(bytes)
+        .map_err(|error| error.to_string())
+        .and_then(|text| Settings::from_config(&text).map_err(|error| error.to_string()));
+
+    match parsed {
+        Ok(settings) => settings,
+        Err(error) => {
+            godot_warn!("Could not load {SETTINGS_FILE}: {error}, using the default settings");
+            Settings::default()
+        }
+    }
+}
+
+pub fn save(settings: &Settings) {
+    storage::write_bytes(SETTINGS_FILE, settings.to_config().as_bytes());
+}
+
+/// Applies the volumes to the audio buses and the display settings to the window.
+///
+/// The other settings are read by the nodes they affect.
+pub fn apply(settings: &Settings) {
+    let mut audio_server = AudioServer::singleton();
+    for volume in VolumeSetting::ALL {
+        let bus = audio_server.get_bus_index(volume.bus());
+        if bus < 0 {
+            godot_warn!("Audio bus \"{}\" not found", volume.bus());
+            continue;
+        }
+
+        // A volume of 0 mutes the bus rather than turning it down to -inf dB.
+        let linear = volume.get(settings);
+        audio_server.set_bus_mute(bus, linear <= 0.0);
+        if linear > 0.0 {
+            audio_server.set_bus_volume_db(bus, linear_to_db(f64::from(linear)) as f32);
+        }
+    }
+
+    // Only switch the window mode when it changes, so a maximized window is left alone.
+    let mut display_server = DisplayServer::singleton();
+    let mode = display_server.window_get_mode();
+    let fullscreen = mode == WindowMode::FULLSCREEN || mode == WindowMode::EXCLUSIVE_FULLSCREEN;
+    if settings.fullscreen != fullscreen {
+        display_server.window_set_mode(if settings.fullscreen {
+            WindowMode::FULLSCREEN
+        } else {
+            WindowMode::WINDOWED
+        });
+    }
+    display_server.window_set_vsync_mode(if settings.vsync {
+        VSyncMode::ENABLED
+    } else {
+        VSyncMode::DISABLED
+    });
+}
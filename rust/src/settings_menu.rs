@@ -0,0 +1,173 @@
+use crate::sim::settings::{Settings, ToggleSetting, VolumeSetting};
+
+use godot::classes::{
+    Button, CheckButton, HBoxContainer, HSlider, IPanelContainer, Label, PanelContainer, VBoxContainer,
+};
+use godot::prelude::*;
+
+
+#[derive(GodotClass)]
+#[class(base=PanelContainer)]
+pub struct SettingsMenu {
+    settings: Settings,
+    // The controls of the menu, in the order of `VolumeSetting::ALL` and `ToggleSetting::ALL`.
+    volume_sliders: Vec<Gd<HSlider>>,
+    toggle_buttons: Vec<Gd<CheckButton>>,
+    base: Base<PanelContainer>,
+}
+
+#[godot_api]
+impl IPanelContainer for SettingsMenu {
+    // This function initializes a new SettingsMenu instance with the default settings.
+    // The game passes in the saved settings with `set_settings` once they are loaded.
+    fn init(base: Base<PanelContainer>) -> Self {
+        Self {
+            settings: Settings::default(),
+            volume_sliders: Vec::new(),
+            toggle_buttons: Vec::new(),
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // Build the menu: a title, a slider for every volume, a check button for every
+        // on/off setting, a button to the controls and a button to close the menu, stacked in a column.
+        let mut rows = VBoxContainer::new_alloc();
+        rows.add_theme_constant_override("separation", 12);
+
+        let mut title = Label::new_alloc();
+        title.set_text("Settings");
+        title.set_horizontal_alignment(godot::global::HorizontalAlignment::CENTER);
+        rows.add_child(&title);
+
+        // The index of the setting is bound to every callback, so one method handles them all.
+        for volume in VolumeSetting::ALL {
+            let mut label = Label::new_alloc();
+            label.set_text(&volume.to_string());
+            label.set_custom_minimum_size(Vector2::new(220.0, 0.0));
+
+            let mut slider = HSlider::new_alloc();
+            slider.set_min(0.0);
+            slider.set_max(1.0);
+            slider.set_step(0.05);
+            slider.set_h_size_flags(godot::classes::control::SizeFlags::EXPAND_FILL);
+            slider.set_custom_minimum_size(Vector2::new(160.0, 24.0));
+            slider.connect(
+                "value_changed",
+                &self
+                    .base()
+                    .callable("on_volume_changed")
+                    .bind(&[(volume.index() as i64).to_variant()]),
+            );
+
+            let mut row = HBoxContainer::new_alloc();
+            row.add_child(&label);
+            row.add_child(&slider);
+            rows.add_child(&row);
+            self.volume_sliders.push(slider);
+        }
+
+        for toggle in ToggleSetting::ALL {
+            let mut check_button = CheckButton::new_alloc();
+            check_button.set_text(&toggle.to_string());
+            check_button.connect(
+                "toggled",
+                &self
+                    .base()
+                    .callable("on_toggle_toggled")
+                    .bind(&[(toggle.index() as i64).to_variant()]),
+            );
+            rows.add_child(&check_button);
+            self.toggle_buttons.push(check_button);
+        }
+
+        let mut controls_button = Button::new_alloc();
+        controls_button.set_text("Controls");
+        controls_button.connect("pressed", &self.base().callable("on_controls_pressed"));
+        rows.add_child(&controls_button);
+
+        let mut back_button = Button::new_alloc();
+        back_button.set_text("Back");
+        back_button.connect("pressed", &self.base().callable("on_back_pressed"));
+        rows.add_child(&back_button);
+
+        self.base_mut().add_child(&rows);
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl SettingsMenu {
+    /// Emitted every time the player changes a setting, so it can be applied straight away.
+    #[signal]
+    pub fn settings_changed();
+
+    /// Emitted when the player closes the menu, which is when the settings are saved.
+    #[signal]
+    pub fn closed();
+
+    /// Emitted when the player asks for the controls menu. The settings menu hides itself first.
+    #[signal]
+    pub fn controls_requested();
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// This function replaces the settings shown in the menu, without emitting `settings_changed`.
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+        self.refresh();
+    }
+
+    /// This function shows the menu with the current settings.
+    pub fn open(&mut self) {
+        self.refresh();
+        self.base_mut().show();
+    }
+
+    /// This function moves the sliders and check buttons to the current settings.
+    /// It does not emit their signals, so nothing is applied twice.
+    fn refresh(&mut self) {
+        for (volume, slider) in VolumeSetting::ALL.into_iter().zip(self.volume_sliders.iter_mut()) {
+            slider.set_value_no_signal(f64::from(volume.get(&self.settings)));
+        }
+        for (toggle, check_button) in ToggleSetting::ALL.into_iter().zip(self.toggle_buttons.iter_mut()) {
+            check_button.set_pressed_no_signal(toggle.get(&self.settings));
+        }
+    }
+
+    #[func]
+    /// This function is called when a volume slider is moved.
+    fn on_volume_changed(&mut self, value: f64, volume: i64) {
+        let Some(volume) = VolumeSetting::from_index(volume) else {
+            return;
+        };
+        volume.set(&mut self.settings, value as f32);
+        self.signals().settings_changed().emit();
+    }
+
+    #[func]
+    /// This function is called when an on/off setting is toggled.
+    fn on_toggle_toggled(&mut self, on: bool, toggle: i64) {
+        let Some(toggle) = ToggleSetting::from_index(toggle) else {
+            return;
+        };
+        toggle.set(&mut self.settings, on);
+        self.signals().settings_changed().emit();
+    }
+
+    #[func]
+    /// This function is called when the "Controls" button is pressed. It hides the menu.
+    fn on_controls_pressed(&mut self) {
+        self.base_mut().hide();
+        self.signals().controls_requested().emit();
+    }
+
+    #[func]
+    /// This function is called when the "Back" button is pressed. It hides the menu.
+    fn on_back_pressed(&mut self) {
+        self.base_mut().hide();
+        self.signals().closed().emit();
+    }
+}
@@ -0,0 +1,288 @@
+use super::behaviour::{Accelerating, Homing, MobBehaviour, Orbiting, SineWave, Splitting, Straight};
+use super::config::{self, ConfigError, Section};
+
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// How a mob moves once it has been spawned, with the behaviour's tuning values.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MovementPattern {
+    /// Flies in a straight line at its spawn speed.
+    #[default]
+    Straight,
+    /// Weaves `amplitude` pixels either side of its heading, `frequency` times a second.
+    SineWave { amplitude: f32, frequency: f32 },
+    /// Turns towards the player at up to `turn_rate` radians per second.
+    Homing { turn_rate: f32 },
+    /// Gains `acceleration` speed per second up to `top_speed`.
+    Accelerating { acceleration: f32, top_speed: f32 },
+    /// Bounces off the first screen edge and splits into two, `spread` radians apart from the bounce.
+    Splitting { spread: f32 },
+    /// Circles the player at `radius` for `duration` seconds at `angular_speed` radians per second.
+    Orbiting { radius: f32, angular_speed: f32, duration: f32 },
+}
+
+impl MovementPattern {
+    /// Reads the `movement` key of a section and the tuning keys of that movement.
+    ///
+    /// | movement     | keys (defaults)                                                  |
+    /// |--------------|------------------------------------------------------------------|
+    /// | straight     |                                                                  |
+    /// | sine         | `amplitude` (60), `frequency` (1.5)                              |
+    /// | homing       | `turn_rate` (1.5)                                                |
+    /// | accelerating | `acceleration` (300), `top_speed` (900)                          |
+    /// | splitting    | `split_angle` (0.4)                                              |
+    /// | orbiting     | `orbit_radius` (120), `orbit_speed` (2.5), `orbit_time` (3)      |
+    pub fn from_section(section: &Section) -> Result<Self, ConfigError> {
+        let number = |key: &str, default: f32| section.number(key, default.into()).map(|value| value as f32);
+
+        Ok(match section.string("movement", "straight")?.as_str() {
+            "straight" => MovementPattern::Straight,
+            "sine" => MovementPattern::SineWave {
+                amplitude: number("amplitude", 60.0)?,
+                frequency: number("frequency", 1.5)?,
+            },
+            "homing" => MovementPattern::Homing {
+                turn_rate: number("turn_rate", 1.5)?,
+            },
+            "accelerating" => MovementPattern::Accelerating {
+                acceleration: number("acceleration", 300.0)?,
+                top_speed: number("top_speed", 900.0)?,
+            },
+            "splitting" => MovementPattern::Splitting {
+                spread: number("split_angle", 0.4)?,
+            },
+            "orbiting" => MovementPattern::Orbiting {
+                radius: number("orbit_radius", 120.0)?,
+                angular_speed: number("orbit_speed", 2.5)?,
+                duration: number("orbit_time", 3.0)?,
+            },
+            name => {
+                return Err(ConfigError::new(
+                    0,
+                    format!("[{}] unknown movement pattern '{name}'", section.name),
+                ))
+            }
+        })
+    }
+
+    /// Creates a fresh behaviour that moves a mob in this pattern.
+    pub fn behaviour(self) -> Box<dyn MobBehaviour> {
+        match self {
+            MovementPattern::Straight => Box::new(Straight),
+            MovementPattern::SineWave { amplitude, frequency } => Box::new(SineWave::new(amplitude, frequency)),
+            MovementPattern::Homing { turn_rate } => Box::new(Homing { turn_rate }),
+            MovementPattern::Accelerating {
+                acceleration,
+                top_speed,
+            } => Box::new(Accelerating {
+                acceleration,
+                top_speed,
+            }),
+            MovementPattern::Splitting { spread } => Box::new(Splitting::new(spread)),
+            MovementPattern::Orbiting {
+                radius,
+                angular_speed,
+                duration,
+            } => Box::new(Orbiting::new(radius, angular_speed, duration)),
+        }
+    }
+}
+
+/// A kind of mob, as described in `mob_types.toml`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MobArchetype {
+    /// The section name in the file, e.g. "fireball".
+    pub name: String,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    /// Multiplier for the size of the sprite and collision shape.
+    pub scale: f32,
+    /// Radius of the collision capsule before `scale` is applied.
+    pub collision_radius: f32,
+    /// Animation of the mob's `AnimatedSprite2D` to play.
+    pub animation: String,
+    pub movement: MovementPattern,
+    /// Relative chance of being picked; 0 disables the archetype.
+    pub spawn_weight: f32,
+    /// The archetype only spawns once the score has reached this value.
+    pub min_score: i32,
+}
+
+impl Default for MobArchetype {
+    /// The original fireball: 300-600 speed, flying straight with the "fly" animation.
+    fn default() -> Self {
+        Self {
+            name: "fireball".to_string(),
+            min_speed: 300.0,
+            max_speed: 600.0,
+            scale: 1.0,
+            collision_radius: 7.0,
+            animation: "fly".to_string(),
+            movement: MovementPattern::Straight,
+            spawn_weight: 1.0,
+            min_score: 0,
+        }
+    }
+}
+
+impl MobArchetype {
+    /// Reads an archetype from a `[name]` section; missing keys keep their default.
+    pub fn from_section(section: &Section) -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let error = |message: &str| ConfigError::new(0, format!("[{}] {message}", section.name));
+
+        let archetype = Self {
+            name: section.name.clone(),
+            min_speed: section.number("min_speed", defaults.min_speed.into())? as f32,
+            max_speed: section.number("max_speed", defaults.max_speed.into())? as f32,
+            scale: section.number("scale", defaults.scale.into())? as f32,
+            collision_radius: section.number("collision_radius", defaults.collision_radius.into())? as f32,
+            animation: section.string("animation", &defaults.animation)?,
+            movement: MovementPattern::from_section(section)?,
+            spawn_weight: section.number("spawn_weight", defaults.spawn_weight.into())? as f32,
+            min_score: section.number("min_score", defaults.min_score.into())? as i32,
+        };
+
+        if archetype.min_speed < 0.0 || archetype.max_speed < archetype.min_speed {
+            return Err(error("needs 0 <= min_speed <= max_speed"));
+        }
+        if archetype.scale <= 0.0 || archetype.collision_radius <= 0.0 {
+            return Err(error("scale and collision_radius must be positive"));
+        }
+        if archetype.spawn_weight < 0.0 {
+            return Err(error("spawn_weight must not be negative"));
+        }
+
+        Ok(archetype)
+    }
+}
+
+/// Reads every archetype in a mob types file, one per section.
+pub fn parse_archetypes(text: &str) -> Result<Vec<MobArchetype>, ConfigError> {
+    let archetypes = config::parse(text)?
+        .iter()
+        .map(MobArchetype::from_section)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !archetypes.iter().any(|archetype| archetype.spawn_weight > 0.0 && archetype.min_score <= 0) {
+        return Err(ConfigError::new(0, "at least one mob type must be able to spawn at score 0"));
+    }
+
+    Ok(archetypes)
+}
+
+/// Picks an archetype that may appear at `score`, weighted by `spawn_weight`.
+///
+/// Returns `None` if no archetype is eligible.
+pub fn pick_archetype<'a, R: Rng + ?Sized>(
+    archetypes: &'a [MobArchetype],
+    score: i32,
+    rng: &mut R,
+) -> Option<&'a MobArchetype> {
+    let eligible: Vec<&MobArchetype> = archetypes
+        .iter()
+        .filter(|archetype| archetype.min_score <= score && archetype.spawn_weight > 0.0)
+        .collect();
+
+    eligible
+        .choose_weighted(rng, |archetype| archetype.spawn_weight)
+        .ok()
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::rng::GameRng;
+
+    fn archetype(name: &str, spawn_weight: f32, min_score: i32) -> MobArchetype {
+        MobArchetype {
+            name: name.to_string(),
+            spawn_weight,
+            min_score,
+            ..MobArchetype::default()
+        }
+    }
+
+    #[test]
+    fn the_shipped_mob_types_are_valid() {
+        let archetypes = parse_archetypes(include_str!("../../../godot/mob_types.toml")).unwrap();
+        assert_eq!(
+            archetypes[0],
+            MobArchetype {
+                spawn_weight: 10.0,
+                ..MobArchetype::default()
+            }
+        );
+    }
+
+    #[test]
+    fn missing_keys_keep_the_fireball_defaults() {
+        let archetypes = parse_archetypes("[bat]\nmovement = \"sine\"\nfrequency = 3\n").unwrap();
+        assert_eq!(
+            archetypes,
+            [MobArchetype {
+                name: "bat".to_string(),
+                movement: MovementPattern::SineWave {
+                    amplitude: 60.0,
+                    frequency: 3.0,
+                },
+                ..MobArchetype::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_archetypes_are_rejected() {
+        for text in [
+            "[a]\nmin_speed = -1\n",
+            "[a]\nmin_speed = 500\nmax_speed = 400\n",
+            "[a]\nscale = 0\n",
+            "[a]\ncollision_radius = -2\n",
+            "[a]\nspawn_weight = -1\n",
+            "[a]\nmovement = \"teleport\"\n",
+            "[a]\nmin_speed = \"fast\"\n",
+        ] {
+            assert!(parse_archetypes(text).is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn some_archetype_must_spawn_at_score_0() {
+        assert!(parse_archetypes("").is_err());
+        assert!(parse_archetypes("[a]\nmin_score = 5\n").is_err());
+        // A zero weight disables an archetype, so it does not count either.
+        assert!(parse_archetypes("[a]\nspawn_weight = 0\n[b]\nmin_score = 5\n").is_err());
+        assert!(parse_archetypes("[a]\nspawn_weight = 0\n[b]\nmin_score = 0\n").is_ok());
+    }
+
+    #[test]
+    fn only_reached_and_enabled_archetypes_are_picked() {
+        let archetypes = [archetype("early", 1.0, 0), archetype("off", 0.0, 0), archetype("late", 1.0, 20)];
+        let mut rng = GameRng::new(3);
+
+        for _ in 0..100 {
+            assert_eq!(pick_archetype(&archetypes, 19, &mut rng).unwrap().name, "early");
+        }
+        let late = (0..100)
+            .filter(|_| pick_archetype(&archetypes, 20, &mut rng).unwrap().name == "late")
+            .count();
+        assert!(late > 0 && late < 100);
+
+        assert_eq!(pick_archetype(&archetypes[1..], 0, &mut rng), None);
+        assert_eq!(pick_archetype(&[], 0, &mut rng), None);
+    }
+
+    #[test]
+    fn picks_follow_the_weights() {
+        let archetypes = [archetype("common", 3.0, 0), archetype("rare", 1.0, 0)];
+        let mut rng = GameRng::new(8);
+
+        let rare = (0..4000)
+            .filter(|_| pick_archetype(&archetypes, 0, &mut rng).unwrap().name == "rare")
+            .count();
+        // A quarter of the picks, give or take.
+        assert!((800..1200).contains(&rare), "{rare}");
+    }
+}
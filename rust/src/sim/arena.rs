@@ -0,0 +1,121 @@
+use super::math::Vec2;
+
+/// The arena size the scenes are laid out for, the viewport size in `project.godot`.
+pub const DESIGN_SIZE: Vec2 = Vec2::new(480.0, 720.0);
+
+/// How far past the arena edge a mob may fly before it is removed.
+pub const DESPAWN_MARGIN: f32 = 100.0;
+
+/// The logical play area, shared by the player's movement, the mob spawns and the mob despawns.
+///
+/// Arena coordinates run from zero to `size`. The arena sits at `offset` on the screen,
+/// which is only away from the corner when the arena is letterboxed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Arena {
+    pub offset: Vec2,
+    pub size: Vec2,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new(DESIGN_SIZE)
+    }
+}
+
+impl Arena {
+    /// An arena of `size` in the corner of the screen.
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            size,
+        }
+    }
+
+    /// Fits the arena to a screen of `screen` size.
+    ///
+    /// The arena fills the screen, unless `max_aspect` is given and the screen is wider than
+    /// that width to height ratio. The arena then keeps to the ratio, centred between bars.
+    pub fn fit(screen: Vec2, max_aspect: Option<f32>) -> Self {
+        let width = match max_aspect {
+            Some(aspect) if aspect > 0.0 => screen.x.min(screen.y * aspect),
+            _ => screen.x,
+        };
+        Self {
+            offset: Vec2::new((screen.x - width) / 2.0, 0.0),
+            size: Vec2::new(width, screen.y),
+        }
+    }
+
+    /// Whether `position` is inside the arena or at most `margin` past its edges.
+    pub fn contains(&self, position: Vec2, margin: f32) -> bool {
+        position.x >= -margin
+            && position.y >= -margin
+            && position.x <= self.size.x + margin
+            && position.y <= self.size.y + margin
+    }
+
+    /// Moves a point laid out for `DESIGN_SIZE`, such as a point of the `MobPath` curve,
+    /// to the same spot relative to this arena.
+    pub fn scale_from_design(&self, point: Vec2) -> Vec2 {
+        Vec2::new(
+            point.x * self.size.x / DESIGN_SIZE.x,
+            point.y * self.size.y / DESIGN_SIZE.y,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_arena_fills_a_screen_without_an_aspect_limit() {
+        let arena = Arena::fit(Vec2::new(1920.0, 1080.0), None);
+        assert_eq!(arena, Arena::new(Vec2::new(1920.0, 1080.0)));
+        assert_eq!(Arena::fit(Vec2::new(1920.0, 1080.0), Some(0.0)), arena);
+    }
+
+    #[test]
+    fn wide_screens_are_letterboxed() {
+        let arena = Arena::fit(Vec2::new(1920.0, 1080.0), Some(1.0));
+        assert_eq!(arena.size, Vec2::new(1080.0, 1080.0));
+        assert_eq!(arena.offset, Vec2::new(420.0, 0.0));
+    }
+
+    #[test]
+    fn tall_screens_use_the_whole_screen() {
+        let arena = Arena::fit(Vec2::new(480.0, 1000.0), Some(DESIGN_SIZE.x / DESIGN_SIZE.y));
+        assert_eq!(arena, Arena::new(Vec2::new(480.0, 1000.0)));
+    }
+
+    #[test]
+    fn contains_reaches_the_margin_and_no_further() {
+        let arena = Arena::default();
+        for inside in [Vec2::ZERO, DESIGN_SIZE, Vec2::new(240.0, 360.0)] {
+            assert!(arena.contains(inside, 0.0), "{inside:?}");
+        }
+        assert!(!arena.contains(Vec2::new(-0.5, 10.0), 0.0));
+
+        let edge = DESIGN_SIZE + Vec2::new(DESPAWN_MARGIN, DESPAWN_MARGIN);
+        assert!(arena.contains(edge, DESPAWN_MARGIN));
+        assert!(arena.contains(Vec2::new(-DESPAWN_MARGIN, -DESPAWN_MARGIN), DESPAWN_MARGIN));
+        for outside in [
+            Vec2::new(-DESPAWN_MARGIN - 1.0, 0.0),
+            Vec2::new(0.0, -DESPAWN_MARGIN - 1.0),
+            Vec2::new(edge.x + 1.0, 0.0),
+            Vec2::new(0.0, edge.y + 1.0),
+        ] {
+            assert!(!arena.contains(outside, DESPAWN_MARGIN), "{outside:?}");
+        }
+    }
+
+    #[test]
+    fn design_points_scale_to_the_arena() {
+        assert_eq!(Arena::default().scale_from_design(Vec2::new(240.0, 720.0)), Vec2::new(240.0, 720.0));
+
+        // Letterboxed points stay in arena coordinates; the offset is applied on screen.
+        let arena = Arena::fit(Vec2::new(1920.0, 1080.0), Some(1.0));
+        assert_eq!(arena.scale_from_design(DESIGN_SIZE), arena.size);
+        assert_eq!(arena.scale_from_design(Vec2::new(240.0, 360.0)), Vec2::new(540.0, 540.0));
+    }
+}
@@ -0,0 +1,183 @@
+use super::config::{self, ConfigError, Section};
+
+use rand::Rng;
+use std::fmt;
+
+/// The audio buses sounds are routed to, as set up in `default_bus_layout.tres`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Bus {
+    Master,
+    Music,
+    #[default]
+    Sfx,
+    Ui,
+}
+
+impl Bus {
+    pub const ALL: [Bus; 4] = [Bus::Master, Bus::Music, Bus::Sfx, Bus::Ui];
+
+    /// The name of the bus in the audio bus layout.
+    pub fn name(self) -> &'static str {
+        match self {
+            Bus::Master => "Master",
+            Bus::Music => "Music",
+            Bus::Sfx => "SFX",
+            Bus::Ui => "UI",
+        }
+    }
+
+    /// The bus named `name` in config files, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|bus| bus.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A named sound with one or more variants, as described in `sounds.toml`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoundGroup {
+    /// The section name in the file, e.g. "fire".
+    pub name: String,
+    /// Resource paths of the variants, e.g. "res://sounds/fire1.mp3".
+    pub streams: Vec<String>,
+    /// Names of `AudioStreamPlayer` nodes under the Main node whose streams are variants too.
+    pub nodes: Vec<String>,
+    pub bus: Bus,
+    /// Volume in decibels before the random variation.
+    pub volume_db: f32,
+    /// Pitch scale before the random variation; below 1 also plays slower.
+    pub pitch: f32,
+    /// The volume varies by up to this many decibels either way.
+    pub volume_variation_db: f32,
+    /// The pitch scale varies by up to this fraction of `pitch` either way.
+    pub pitch_variation: f32,
+}
+
+impl Default for SoundGroup {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            streams: Vec::new(),
+            nodes: Vec::new(),
+            bus: Bus::default(),
+            volume_db: 0.0,
+            pitch: 1.0,
+            volume_variation_db: 0.0,
+            pitch_variation: 0.0,
+        }
+    }
+}
+
+impl SoundGroup {
+    /// Reads a sound group from a `[name]` section; missing keys keep their default.
+    pub fn from_section(section: &Section) -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let error = |message: &str| ConfigError::new(0, format!("[{}] {message}", section.name));
+
+        let bus_name = section.string("bus", defaults.bus.name())?;
+        let group = Self {
+            name: section.name.clone(),
+            streams: section.list("streams")?,
+            nodes: section.list("nodes")?,
+            bus: Bus::from_name(&bus_name).ok_or_else(|| error(&format!("unknown bus \"{bus_name}\"")))?,
+            volume_db: section.number("volume_db", defaults.volume_db.into())? as f32,
+            pitch: section.number("pitch", defaults.pitch.into())? as f32,
+            volume_variation_db: section.number("volume_variation_db", defaults.volume_variation_db.into())? as f32,
+            pitch_variation: section.number("pitch_variation", defaults.pitch_variation.into())? as f32,
+        };
+
+        if group.streams.is_empty() && group.nodes.is_empty() {
+            return Err(error("needs at least one of streams or nodes"));
+        }
+        if group.pitch <= 0.0 {
+            return Err(error("pitch must be positive"));
+        }
+        if group.volume_variation_db < 0.0 || !(0.0..1.0).contains(&group.pitch_variation) {
+            return Err(error("needs volume_variation_db >= 0 and 0 <= pitch_variation < 1"));
+        }
+
+        Ok(group)
+    }
+
+    /// A random pitch scale and volume for one playback.
+    pub fn variation<R: Rng + ?Sized>(&self, rng: &mut R) -> Variation {
+        let spread = |amount: f32, rng: &mut R| {
+            if amount > 0.0 {
+                rng.random_range(-amount..=amount)
+            } else {
+                0.0
+            }
+        };
+
+        Variation {
+            pitch_scale: self.pitch * (1.0 + spread(self.pitch_variation, rng)),
+            volume_db: self.volume_db + spread(self.volume_variation_db, rng),
+        }
+    }
+}
+
+/// How one playback of a sound differs from the recording.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Variation {
+    pub pitch_scale: f32,
+    pub volume_db: f32,
+}
+
+/// Picks random variants of a sound without playing the same one twice in a row.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VariantPicker {
+    last: Option<usize>,
+}
+
+impl VariantPicker {
+    /// Picks one of `count` variants, never the last one picked unless it is the only one.
+    ///
+    /// Returns `None` if there are no variants.
+    pub fn pick<R: Rng + ?Sized>(&mut self, count: usize, rng: &mut R) -> Option<usize> {
+        let pick = match (count, self.last) {
+            (0, _) => return None,
+            (1, _) => 0,
+            // Pick among the others by skipping over the last one.
+            (_, Some(last)) if last < count => {
+                let pick = rng.random_range(0..count - 1);
+                if pick >= last {
+                    pick + 1
+                } else {
+                    pick
+                }
+            }
+            _ => rng.random_range(0..count),
+        };
+
+        self.last = Some(pick);
+        Some(pick)
+    }
+}
+
+/// Reads every sound group in a sounds file, one per section.
+pub fn parse_sound_groups(text: &str) -> Result<Vec<SoundGroup>, ConfigError> {
+    config::parse(text)?
+        .iter()
+        .map(SoundGroup::from_section)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_shipped_sound_groups_are_valid() {
+        let groups = parse_sound_groups(include_str!("../../../godot/sounds.toml")).unwrap();
+        let fire = groups.iter().find(|group| group.name == "fire").unwrap();
+        assert_eq!(fire.streams.len(), 5);
+        assert_eq!(fire.streams[0], "res://sounds/fire1.mp3");
+    }
+}
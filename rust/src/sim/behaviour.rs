@@ -0,0 +1,423 @@
+use super::math::Vec2;
+
+use std::f32::consts::TAU;
+use std::fmt;
+
+/// The part of a mob's state its behaviour can read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MobState {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// What a behaviour knows about the rest of the world.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BehaviourContext {
+    /// Position of the player, if they are alive.
+    pub player: Option<Vec2>,
+    /// Size of the play area, which spans from the origin to this corner.
+    pub arena: Vec2,
+}
+
+/// The result of one behaviour step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Steering {
+    /// Velocity the mob should have for the next step.
+    pub velocity: Vec2,
+    /// If set, a second mob should be spawned at the mob's position with this velocity.
+    pub split: Option<Vec2>,
+}
+
+impl Steering {
+    pub fn velocity(velocity: Vec2) -> Self {
+        Self {
+            velocity,
+            split: None,
+        }
+    }
+}
+
+/// How a mob moves once it has been spawned.
+///
+/// A behaviour is stepped once per physics frame with a fixed `delta` and
+/// returns the velocity for the next frame; the engine (or `World` in the
+/// simulation) then moves the mob by that velocity.
+pub trait MobBehaviour: fmt::Debug {
+    fn steer(&mut self, mob: &MobState, context: &BehaviourContext, delta: f32) -> Steering;
+}
+
+/// Keeps the spawn velocity.
+#[derive(Clone, Debug, Default)]
+pub struct Straight;
+
+impl MobBehaviour for Straight {
+    fn steer(&mut self, mob: &MobState, _context: &BehaviourContext, _delta: f32) -> Steering {
+        Steering::velocity(mob.velocity)
+    }
+}
+
+/// Weaves from side to side across its spawn heading.
+#[derive(Clone, Debug)]
+pub struct SineWave {
+    /// Largest sideways distance from the straight path.
+    pub amplitude: f32,
+    /// Full side-to-side cycles per second.
+    pub frequency: f32,
+    forward: Option<Vec2>,
+    age: f32,
+}
+
+impl SineWave {
+    pub fn new(amplitude: f32, frequency: f32) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            forward: None,
+            age: 0.0,
+        }
+    }
+}
+
+impl MobBehaviour for SineWave {
+    fn steer(&mut self, mob: &MobState, _context: &BehaviourContext, delta: f32) -> Steering {
+        // The spawn velocity is the centre line the mob weaves around.
+        let forward = *self.forward.get_or_insert(mob.velocity);
+        self.age += delta;
+
+        // The sideways offset is amplitude * sin(wt), so its speed is amplitude * w * cos(wt).
+        let omega = TAU * self.frequency;
+        let side = forward.normalized().rotated(TAU / 4.0);
+        let sideways = self.amplitude * omega * (omega * self.age).cos();
+
+        Steering::velocity(forward + side * sideways)
+    }
+}
+
+/// Turns towards the player, but no faster than `turn_rate`.
+#[derive(Clone, Debug)]
+pub struct Homing {
+    /// Maximum turn in radians per second.
+    pub turn_rate: f32,
+}
+
+impl MobBehaviour for Homing {
+    fn steer(&mut self, mob: &MobState, context: &BehaviourContext, delta: f32) -> Steering {
+        let Some(player) = context.player else {
+            return Steering::velocity(mob.velocity);
+        };
+
+        let wanted = (player - mob.position).angle();
+        let heading = mob.velocity.angle();
+        let max_turn = self.turn_rate * delta;
+        let turn = angle_difference(heading, wanted).clamp(-max_turn, max_turn);
+
+        Steering::velocity(mob.velocity.rotated(turn))
+    }
+}
+
+/// Speeds up along its heading until it reaches `top_speed`.
+#[derive(Clone, Debug)]
+pub struct Accelerating {
+    /// Speed gained per second.
+    pub acceleration: f32,
+    pub top_speed: f32,
+}
+
+impl MobBehaviour for Accelerating {
+    fn steer(&mut self, mob: &MobState, _context: &BehaviourContext, delta: f32) -> Steering {
+        let speed = (mob.velocity.length() + self.acceleration * delta).min(self.top_speed);
+        Steering::velocity(mob.velocity.normalized() * speed)
+    }
+}
+
+/// Bounces off the first screen edge it reaches and splits in two.
+///
+/// The two halves fly off `spread` radians either side of the bounce direction
+/// and do not split again.
+#[derive(Clone, Debug)]
+pub struct Splitting {
+    pub spread: f32,
+    // Mobs spawn on the edge, so bouncing only starts once the mob is inside.
+    entered: bool,
+    split: bool,
+}
+
+impl Splitting {
+    pub fn new(spread: f32) -> Self {
+        Self {
+            spread,
+            entered: false,
+            split: false,
+        }
+    }
+}
+
+impl MobBehaviour for Splitting {
+    fn steer(&mut self, mob: &MobState, context: &BehaviourContext, _delta: f32) -> Steering {
+        let position = mob.position;
+        let arena = context.arena;
+        let inside = position.x > 0.0 && position.y > 0.0 && position.x < arena.x && position.y < arena.y;
+
+        if !self.entered || self.split {
+            self.entered |= inside;
+            return Steering::velocity(mob.velocity);
+        }
+
+        // Reflect the velocity off whichever edge the mob is crossing.
+        let mut bounced = mob.velocity;
+        if (position.x <= 0.0 && bounced.x < 0.0) || (position.x >= arena.x && bounced.x > 0.0) {
+            bounced.x = -bounced.x;
+        }
+        if (position.y <= 0.0 && bounced.y < 0.0) || (position.y >= arena.y && bounced.y > 0.0) {
+            bounced.y = -bounced.y;
+        }
+
+        if bounced == mob.velocity {
+            return Steering::velocity(mob.velocity);
+        }
+
+        self.split = true;
+        Steering {
+            velocity: bounced.rotated(self.spread),
+            split: Some(bounced.rotated(-self.spread)),
+        }
+    }
+}
+
+/// Flies at the player, circles them for a while, then flies off.
+#[derive(Clone, Debug)]
+pub struct Orbiting {
+    pub radius: f32,
+    /// Radians per second around the player; negative values circle the other way.
+    pub angular_speed: f32,
+    /// Seconds spent circling before leaving.
+    pub duration: f32,
+    phase: OrbitPhase,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OrbitPhase {
+    Approach,
+    Orbit { angle: f32, time: f32 },
+    Leave,
+}
+
+impl Orbiting {
+    pub fn new(radius: f32, angular_speed: f32, duration: f32) -> Self {
+        Self {
+            radius,
+            angular_speed,
+            duration,
+            phase: OrbitPhase::Approach,
+        }
+    }
+}
+
+impl MobBehaviour for Orbiting {
+    fn steer(&mut self, mob: &MobState, context: &BehaviourContext, delta: f32) -> Steering {
+        let speed = mob.velocity.length();
+
+        // Without a player to circle the mob just keeps going.
+        let Some(player) = context.player else {
+            self.phase = OrbitPhase::Leave;
+            return Steering::velocity(mob.velocity);
+        };
+
+        match self.phase {
+            OrbitPhase::Approach => {
+                let offset = mob.position - player;
+                if offset.length() <= self.radius {
+                    self.phase = OrbitPhase::Orbit {
+                        angle: offset.angle(),
+                        time: 0.0,
+                    };
+                    return self.steer(mob, context, delta);
+                }
+                Steering::velocity((player - mob.position).normalized() * speed)
+            }
+            OrbitPhase::Orbit { angle, time } => {
+                let time = time + delta;
+                let angle = angle + self.angular_speed * delta;
+
+                if time >= self.duration {
+                    // Fly off along the tangent of the circle.
+                    self.phase = OrbitPhase::Leave;
+                    let tangent = Vec2::from_angle(angle).rotated(TAU / 4.0 * self.angular_speed.signum());
+                    return Steering::velocity(tangent * speed.max(1.0));
+                }
+
+                self.phase = OrbitPhase::Orbit { angle, time };
+
+                // Aim for the next point on the circle around the player's current position.
+                let target = player + Vec2::from_angle(angle) * self.radius;
+                let velocity = if delta > 0.0 {
+                    (target - mob.position) * (1.0 / delta)
+                } else {
+                    mob.velocity
+                };
+                Steering::velocity(velocity)
+            }
+            OrbitPhase::Leave => Steering::velocity(mob.velocity),
+        }
+    }
+}
+
+/// The signed smallest angle that turns `from` into `to`, in `-PI..=PI`.
+pub fn angle_difference(from: f32, to: f32) -> f32 {
+    let difference = (to - from).rem_euclid(TAU);
+    if difference > TAU / 2.0 {
+        difference - TAU
+    } else {
+        difference
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA: f32 = 1.0 / 60.0;
+
+    const ARENA: Vec2 = Vec2::new(480.0, 720.0);
+
+    // Steps a mob the way the engine does: steer, then move with the new velocity.
+    fn step(behaviour: &mut dyn MobBehaviour, mob: &mut MobState, player: Option<Vec2>) -> Steering {
+        let context = BehaviourContext { player, arena: ARENA };
+        let steering = behaviour.steer(mob, &context, DELTA);
+        mob.velocity = steering.velocity;
+        mob.position += mob.velocity * DELTA;
+        steering
+    }
+
+    #[test]
+    fn sine_wave_weaves_with_its_amplitude_and_period() {
+        let mut wave = SineWave::new(40.0, 1.0);
+        let mut mob = MobState {
+            position: Vec2::ZERO,
+            velocity: Vec2::new(100.0, 0.0),
+        };
+
+        // The sideways offset peaks at the amplitude a quarter period in,
+        // and is back on the centre line after a full period.
+        let mut peak: f32 = 0.0;
+        for _ in 0..60 {
+            step(&mut wave, &mut mob, None);
+            peak = peak.max(mob.position.y.abs());
+        }
+        // Stepping at 60 Hz overshoots the exact sine by up to half a step of sideways speed.
+        let step_error = 40.0 * TAU * DELTA / 2.0;
+        assert!((peak - 40.0).abs() <= step_error, "peak {peak}");
+        assert!(mob.position.y.abs() < 1.0, "offset {}", mob.position.y);
+        // The forward speed is untouched.
+        assert!((mob.position.x - 100.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn homing_never_turns_faster_than_its_turn_rate() {
+        let mut homing = Homing { turn_rate: 2.0 };
+        let mut mob = MobState {
+            position: Vec2::new(240.0, 0.0),
+            velocity: Vec2::new(0.0, -200.0),
+        };
+        let player = Vec2::new(240.0, 600.0);
+
+        for _ in 0..120 {
+            let heading = mob.velocity.angle();
+            step(&mut homing, &mut mob, Some(player));
+            let turn = angle_difference(heading, mob.velocity.angle()).abs();
+            assert!(turn <= 2.0 * DELTA + 1e-5, "turned {turn}");
+            assert!((mob.velocity.length() - 200.0).abs() < 1e-2);
+        }
+
+        // Given enough time it faces the player.
+        let wanted = (player - mob.position).angle();
+        assert!(angle_difference(mob.velocity.angle(), wanted).abs() < 0.05);
+    }
+
+    #[test]
+    fn accelerating_stops_at_its_top_speed() {
+        let mut accelerating = Accelerating {
+            acceleration: 300.0,
+            top_speed: 500.0,
+        };
+        let mut mob = MobState {
+            position: Vec2::ZERO,
+            velocity: Vec2::new(0.0, 200.0),
+        };
+
+        // 300 per second takes one second to go from 200 to 500.
+        for _ in 0..30 {
+            step(&mut accelerating, &mut mob, None);
+        }
+        assert!((mob.velocity.length() - 350.0).abs() < 1e-2);
+
+        for _ in 0..120 {
+            step(&mut accelerating, &mut mob, None);
+            assert!(mob.velocity.length() <= 500.0 + 1e-3);
+        }
+        assert!((mob.velocity.length() - 500.0).abs() < 1e-3);
+        assert!(mob.velocity.x.abs() < 1e-3);
+    }
+
+    #[test]
+    fn splitting_splits_exactly_once() {
+        let mut splitting = Splitting::new(0.3);
+        // Spawned on the top edge, flying down and slightly right.
+        let mut mob = MobState {
+            position: Vec2::new(240.0, 0.0),
+            velocity: Vec2::new(50.0, 400.0),
+        };
+
+        let mut splits = Vec::new();
+        for _ in 0..600 {
+            if let Some(split) = step(&mut splitting, &mut mob, None).split {
+                splits.push((mob.velocity, split));
+            }
+        }
+
+        assert_eq!(splits.len(), 1);
+        // Both halves bounce back up from the bottom edge.
+        let (velocity, split) = splits[0];
+        assert!(velocity.y < 0.0 && split.y < 0.0);
+        assert!((angle_difference(split.angle(), velocity.angle()) - 0.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn orbiting_approaches_circles_and_leaves() {
+        let mut orbiting = Orbiting::new(80.0, 2.0, 1.0);
+        let player = Vec2::new(240.0, 360.0);
+        let mut mob = MobState {
+            position: Vec2::new(240.0, 0.0),
+            velocity: Vec2::new(0.0, 300.0),
+        };
+
+        // Approach: fly at the player until within the radius.
+        let mut frames = 0;
+        while orbiting.phase == OrbitPhase::Approach {
+            step(&mut orbiting, &mut mob, Some(player));
+            frames += 1;
+            assert!(frames < 120);
+        }
+
+        // Orbit: stay on the circle for `duration` seconds.
+        let mut orbit_frames = 0;
+        while matches!(orbiting.phase, OrbitPhase::Orbit { .. }) {
+            assert!((mob.position.distance_to(player) - 80.0).abs() < 1.0);
+            step(&mut orbiting, &mut mob, Some(player));
+            orbit_frames += 1;
+        }
+        assert!((59..=61).contains(&orbit_frames), "orbited {orbit_frames} frames");
+
+        // Leave: keep flying straight, even though the player is still there.
+        assert_eq!(orbiting.phase, OrbitPhase::Leave);
+        let velocity = mob.velocity;
+        step(&mut orbiting, &mut mob, Some(player));
+        assert_eq!(mob.velocity, velocity);
+    }
+
+    #[test]
+    fn angle_difference_takes_the_short_way() {
+        assert!((angle_difference(0.1, TAU - 0.1) + 0.2).abs() < 1e-5);
+        assert!((angle_difference(TAU - 0.1, 0.1) - 0.2).abs() < 1e-5);
+    }
+}
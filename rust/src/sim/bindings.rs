@@ -0,0 +1,201 @@
+use super::config::{self, ConfigError, Section, Value};
+
+use std::fmt;
+
+/// How many keys and buttons can be bound to one action.
+pub const MAX_BINDINGS: usize = 3;
+
+/// An input action players can rebind, as named in the input map of `project.godot`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Dash,
+    StartGame,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Dash,
+        Action::StartGame,
+        Action::Pause,
+    ];
+
+    /// The name of the action in the input map and in the bindings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::Dash => "dash",
+            Action::StartGame => "start_game",
+            Action::Pause => "pause",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: i64) -> Option<Self> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::Dash => "Dash",
+            Action::StartGame => "Start game",
+            Action::Pause => "Pause",
+        })
+    }
+}
+
+/// A key or gamepad button that triggers an action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    /// A key by its physical keycode, so the binding stays in place on any keyboard layout.
+    Key(i32),
+    /// A gamepad button by its index, on any gamepad.
+    Button(i32),
+}
+
+impl Binding {
+    /// Reads a binding written by `to_config`, e.g. "key:81" or "button:0".
+    pub fn from_config(text: &str) -> Option<Self> {
+        let (kind, code) = text.split_once(':')?;
+        let code = code.trim().parse().ok()?;
+        match kind.trim() {
+            "key" => Some(Binding::Key(code)),
+            "button" => Some(Binding::Button(code)),
+            _ => None,
+        }
+    }
+
+    pub fn to_config(self) -> String {
+        match self {
+            Binding::Key(code) => format!("key:{code}"),
+            Binding::Button(index) => format!("button:{index}"),
+        }
+    }
+}
+
+/// The name of a gamepad button, using the Xbox controller's labels.
+pub fn button_name(index: i32) -> String {
+    let name = match index {
+        0 => "A",
+        1 => "B",
+        2 => "X",
+        3 => "Y",
+        4 => "Back",
+        5 => "Guide",
+        6 => "Start",
+        7 => "Left Stick",
+        8 => "Right Stick",
+        9 => "LB",
+        10 => "RB",
+        11 => "D-pad Up",
+        12 => "D-pad Down",
+        13 => "D-pad Left",
+        14 => "D-pad Right",
+        _ => return format!("Gamepad {index}"),
+    };
+    format!("Gamepad {name}")
+}
+
+/// The keys and buttons bound to every action.
+///
+/// A binding triggers at most one action, so binding it to another takes it away from the first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bindings {
+    // Indexed by `Action::index`.
+    bindings: [Vec<Binding>; Action::ALL.len()],
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        &self.bindings[action.index()]
+    }
+
+    /// The action `binding` is bound to, if any.
+    pub fn action_of(&self, binding: Binding) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| self.get(*action).contains(&binding))
+    }
+
+    /// Adds `binding` to `action`, dropping the action's oldest binding if it already has
+    /// `MAX_BINDINGS`.
+    ///
+    /// Returns the other action the binding was taken away from, if it was bound elsewhere.
+    pub fn bind(&mut self, action: Action, binding: Binding) -> Option<Action> {
+        if self.get(action).contains(&binding) {
+            return None;
+        }
+
+        let conflict = self.action_of(binding);
+        if let Some(other) = conflict {
+            self.bindings[other.index()].retain(|existing| *existing != binding);
+        }
+
+        let bindings = &mut self.bindings[action.index()];
+        bindings.push(binding);
+        if bindings.len() > MAX_BINDINGS {
+            bindings.remove(0);
+        }
+
+        conflict
+    }
+
+    /// Removes every binding of `action`.
+    pub fn clear(&mut self, action: Action) {
+        self.bindings[action.index()].clear();
+    }
+
+    /// Reads bindings written by `to_config`. Actions missing from the file keep
+    /// their binding in `defaults`, so new actions get bound on update.
+    pub fn from_config(text: &str, defaults: &Bindings) -> Result<Self, ConfigError> {
+        let sections = config::parse(text)?;
+        let Some(section) = sections.iter().find(|section| section.name == "bindings") else {
+            return Ok(defaults.clone());
+        };
+
+        let mut bindings = defaults.clone();
+        for action in Action::ALL.into_iter().filter(|action| section.get(action.name()).is_some()) {
+            bindings.clear(action);
+            for text in section.list(action.name())? {
+                let binding = Binding::from_config(&text).ok_or_else(|| {
+                    ConfigError::new(0, format!("[bindings] {}: unknown binding \"{text}\"", action.name()))
+                })?;
+                bindings.bind(action, binding);
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    /// Writes the bindings in the config file format read by `from_config`.
+    pub fn to_config(&self) -> String {
+        let mut section = Section::new("bindings");
+        for action in Action::ALL {
+            let list = self.get(action).iter().map(|binding| Value::String(binding.to_config())).collect();
+            section.set(action.name(), Value::List(list));
+        }
+        config::write(&[section])
+    }
+}
@@ -0,0 +1,36 @@
+use super::math::Vec2;
+
+/// A circular hit box.
+///
+/// The scenes use capsules, but circles are close enough for the headless
+/// simulation and keep the overlap test trivial.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Circle {
+    pub fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Returns true if the two circles touch or overlap.
+    pub fn overlaps(&self, other: &Circle) -> bool {
+        let reach = self.radius + other.radius;
+        (other.center - self.center).length_squared() <= reach * reach
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touching_circles_overlap() {
+        let a = Circle::new(Vec2::ZERO, 10.0);
+        assert!(a.overlaps(&Circle::new(Vec2::new(15.0, 0.0), 5.0)));
+        assert!(a.overlaps(&Circle::new(Vec2::new(3.0, 4.0), 1.0)));
+        assert!(!a.overlaps(&Circle::new(Vec2::new(15.1, 0.0), 5.0)));
+    }
+}
@@ -0,0 +1,429 @@
+use std::fmt;
+
+/// A value in a config file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<Value>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Number(value) => write!(f, "{value}"),
+            Value::String(value) => {
+                write!(f, "\"")?;
+                for c in value.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        c => write!(f, "{c}")?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// A `[name]` section and its `key = value` entries, in file order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Section {
+    pub name: String,
+    pub entries: Vec<(String, Value)>,
+}
+
+/// Why a config file could not be used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    /// 1-based line number, or 0 if the problem is not tied to a line.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: {}", self.line, self.message)
+        } else {
+            f.write_str(&self.message)
+        }
+    }
+}
+
+impl Section {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets `key`, replacing an existing entry or appending a new one.
+    pub fn set(&mut self, key: &str, value: Value) {
+        match self.entries.iter_mut().find(|(name, _)| name == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((key.to_string(), value)),
+        }
+    }
+
+    /// Reads a number, using `default` if the key is missing.
+    pub fn number(&self, key: &str, default: f64) -> Result<f64, ConfigError> {
+        match self.get(key) {
+            None => Ok(default),
+            Some(Value::Number(value)) => Ok(*value),
+            Some(_) => Err(self.type_error(key, "a number")),
+        }
+    }
+
+    /// Reads a string, using `default` if the key is missing.
+    pub fn string(&self, key: &str, default: &str) -> Result<String, ConfigError> {
+        match self.get(key) {
+            None => Ok(default.to_string()),
+            Some(Value::String(value)) => Ok(value.clone()),
+            Some(_) => Err(self.type_error(key, "a string")),
+        }
+    }
+
+    /// Reads a list of strings, e.g. `["a", "b", "c"]`; empty if the key is missing.
+    ///
+    /// A single string is read as a comma separated list, e.g. `"a, b, c"`,
+    /// the only way to write a list before arrays were supported.
+    pub fn list(&self, key: &str) -> Result<Vec<String>, ConfigError> {
+        match self.get(key) {
+            None => Ok(Vec::new()),
+            Some(Value::String(value)) => Ok(value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()),
+            Some(Value::List(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Value::String(value) => Ok(value.clone()),
+                    _ => Err(self.type_error(key, "a list of strings")),
+                })
+                .collect(),
+            Some(_) => Err(self.type_error(key, "a list of strings")),
+        }
+    }
+
+    /// Reads a boolean, using `default` if the key is missing.
+    pub fn boolean(&self, key: &str, default: bool) -> Result<bool, ConfigError> {
+        match self.get(key) {
+            None => Ok(default),
+            Some(Value::Bool(value)) => Ok(*value),
+            Some(_) => Err(self.type_error(key, "true or false")),
+        }
+    }
+
+    fn type_error(&self, key: &str, expected: &str) -> ConfigError {
+        ConfigError::new(0, format!("[{}] {key} must be {expected}", self.name))
+    }
+}
+
+/// Parses the small TOML subset used by the game's data files.
+///
+/// Supported: `[section]` headers, `key = value` lines with numbers, `true`/`false`,
+/// double-quoted strings or `[...]` arrays of those, and `#` comments. An array may
+/// span several lines. Entries before the first header go into a section with an
+/// empty name.
+pub fn parse(text: &str) -> Result<Vec<Section>, ConfigError> {
+    let mut sections = vec![Section::default()];
+    let mut lines = text.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let number = index + 1;
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header
+                .strip_suffix(']')
+                .ok_or_else(|| ConfigError::new(number, "section header is missing ']'"))?
+                .trim();
+            sections.push(Section::new(name));
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| ConfigError::new(number, "expected 'key = value'"))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ConfigError::new(number, "missing key"));
+        }
+
+        let mut value = value.trim().to_string();
+        // An array continues on the following lines until its closing bracket.
+        if value.starts_with('[') {
+            while !value.ends_with(']') {
+                let (_, next) = lines
+                    .next()
+                    .ok_or_else(|| ConfigError::new(number, "array is missing its closing ']'"))?;
+                value.push(' ');
+                value.push_str(strip_comment(next).trim());
+            }
+        }
+
+        let value = parse_value(&value).map_err(|message| ConfigError::new(number, message))?;
+
+        // `sections` always holds at least the unnamed section.
+        if let Some(section) = sections.last_mut() {
+            section.set(key, value);
+        }
+    }
+
+    // Drop the unnamed section if nothing was written before the first header.
+    if sections[0].entries.is_empty() {
+        sections.remove(0);
+    }
+
+    Ok(sections)
+}
+
+/// Writes sections back out in the format read by `parse`.
+pub fn write(sections: &[Section]) -> String {
+    let mut text = String::new();
+
+    for section in sections {
+        if !section.name.is_empty() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("[{}]\n", section.name));
+        }
+        for (key, value) in &section.entries {
+            text.push_str(&format!("{key} = {value}\n"));
+        }
+    }
+
+    text
+}
+
+/// Removes a trailing `#` comment, ignoring `#` inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+fn parse_value(text: &str) -> Result<Value, String> {
+    match text {
+        "true" => return Ok(Value::Bool(true)),
+        "false" => return Ok(Value::Bool(false)),
+        _ => {}
+    }
+
+    if let Some(quoted) = text.strip_prefix('"') {
+        let inner = quoted
+            .strip_suffix('"')
+            .ok_or_else(|| "string is missing its closing quote".to_string())?;
+        return unescape(inner).map(Value::String);
+    }
+
+    if let Some(bracketed) = text.strip_prefix('[') {
+        let inner = bracketed
+            .strip_suffix(']')
+            .ok_or_else(|| "array is missing its closing ']'".to_string())?;
+        let mut items = split_items(inner);
+        // A trailing comma leaves an empty last item.
+        if items.last().is_some_and(|item| item.is_empty()) {
+            items.pop();
+        }
+        return items
+            .into_iter()
+            .map(|item| match item {
+                "" => Err("array has an empty item".to_string()),
+                item if item.starts_with('[') => Err("arrays cannot be nested".to_string()),
+                item => parse_value(item),
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::List);
+    }
+
+    let number = text
+        .replace('_', "")
+        .parse::<f64>()
+        .map_err(|_| format!("'{text}' is not a number, string or boolean"))?;
+
+    // `f64::from_str` also reads "inf" and "nan", which no setting can make sense of.
+    if !number.is_finite() {
+        return Err(format!("'{text}' is not a finite number"));
+    }
+    Ok(Value::Number(number))
+}
+
+/// Splits the inside of an array at its commas, ignoring commas inside strings.
+fn split_items(text: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                items.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(text[start..].trim());
+
+    items
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '"' {
+            return Err("string has an unescaped quote".to_string());
+        }
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            other => return Err(format!("unsupported escape '\\{}'", other.unwrap_or(' '))),
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_sections_and_values() {
+        let sections = parse("top = 1\n\n[mob] # a comment\nname = \"bat # 1\"\nspeed = 1_000.5\nfast = true\n").unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].number("top", 0.0), Ok(1.0));
+
+        let mob = &sections[1];
+        assert_eq!(mob.name, "mob");
+        assert_eq!(mob.string("name", ""), Ok("bat # 1".to_string()));
+        assert_eq!(mob.number("speed", 0.0), Ok(1000.5));
+        assert_eq!(mob.boolean("fast", false), Ok(true));
+        assert_eq!(mob.number("missing", 4.0), Ok(4.0));
+    }
+
+    #[test]
+    fn non_finite_numbers_are_rejected_with_their_line() {
+        for value in ["inf", "-inf", "infinity", "nan", "NaN"] {
+            let error = parse(&format!("[mob]\nmax_speed = {value}\n")).unwrap_err();
+            assert_eq!(error.line, 2, "{value}");
+        }
+    }
+
+    #[test]
+    fn written_sections_read_back() {
+        let mut section = Section::new("audio");
+        section.set("volume", Value::Number(0.5));
+        section.set("name", Value::String("a \"quoted\" name".to_string()));
+        section.set(
+            "streams",
+            Value::List(vec![Value::String("a, b".to_string()), Value::String("c".to_string())]),
+        );
+        let sections = parse(&write(&[section.clone()])).unwrap();
+        assert_eq!(sections, vec![section]);
+    }
+
+    #[test]
+    fn arrays_are_read_on_one_or_several_lines() {
+        let text = "[sounds]\none = [\"a\", \"b, c\"]\nempty = []\nmixed = [1, true, \"x\",]\nlong = [\n  \"a\", # first\n  \"b\",\n]\nafter = 2\n";
+        let section = &parse(text).unwrap()[0];
+        assert_eq!(section.list("one"), Ok(vec!["a".to_string(), "b, c".to_string()]));
+        assert_eq!(section.list("empty"), Ok(Vec::new()));
+        assert_eq!(
+            section.get("mixed"),
+            Some(&Value::List(vec![
+                Value::Number(1.0),
+                Value::Bool(true),
+                Value::String("x".to_string())
+            ]))
+        );
+        assert_eq!(section.list("long"), Ok(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(section.number("after", 0.0), Ok(2.0));
+    }
+
+    #[test]
+    fn lists_also_read_comma_separated_strings() {
+        let section = &parse("[sounds]\nstreams = \"a, b ,, c\"\n").unwrap()[0];
+        assert_eq!(section.list("streams"), Ok(vec!["a".to_string(), "b".to_string(), "c".to_string()]));
+        assert_eq!(section.list("missing"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn bad_arrays_are_rejected() {
+        for (text, line) in [
+            ("[a]\nx = [\"a\"\ny = 1\n", 2),
+            ("[a]\nx = [\"a\", , \"b\"]\n", 2),
+            ("[a]\nx = [[\"a\"]]\n", 2),
+            ("[a]\nx = [\"a\" \"b\"]\n", 2),
+        ] {
+            assert_eq!(parse(text).unwrap_err().line, line, "{text:?}");
+        }
+
+        let section = &parse("[a]\nx = [1, 2]\ny = 3\n").unwrap()[0];
+        assert!(section.list("x").is_err());
+        assert!(section.list("y").is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use super::math::Vec2;
+
+/// Tuning values of the dash.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DashConfig {
+    /// Pixels covered by one dash.
+    pub distance: f32,
+    /// Seconds the dash movement takes.
+    pub duration: f32,
+    /// Seconds the player cannot be hit, counted from the start of the dash.
+    pub invulnerability: f32,
+    /// Seconds from the start of one dash until the next one is possible.
+    pub cooldown: f32,
+}
+
+impl Default for DashConfig {
+    fn default() -> Self {
+        Self {
+            distance: 160.0,
+            duration: 0.15,
+            invulnerability: 0.25,
+            cooldown: 1.5,
+        }
+    }
+}
+
+/// The player's dash: a short burst of movement with invulnerability and a cooldown.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Dash {
+    pub config: DashConfig,
+    // Unit vector of the dash in progress.
+    direction: Vec2,
+    // Seconds of dash movement left.
+    moving: f32,
+    // Seconds of invulnerability left.
+    invulnerable: f32,
+    // Seconds until the next dash is possible.
+    cooldown: f32,
+}
+
+impl Dash {
+    pub fn new(config: DashConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Starts a dash towards `direction`, if the cooldown allows it.
+    ///
+    /// Returns whether the dash started.
+    pub fn try_start(&mut self, direction: Vec2) -> bool {
+        if !self.is_ready() || direction.length_squared() == 0.0 {
+            return false;
+        }
+
+        self.direction = direction.normalized();
+        self.moving = self.config.duration;
+        self.invulnerable = self.config.invulnerability;
+        self.cooldown = self.config.cooldown;
+        true
+    }
+
+    /// Counts the dash down by `delta` seconds.
+    ///
+    /// Returns `true` on the step the dash movement ends.
+    pub fn step(&mut self, delta: f32) -> bool {
+        let was_moving = self.is_dashing();
+        self.moving = (self.moving - delta).max(0.0);
+        self.invulnerable = (self.invulnerable - delta).max(0.0);
+        self.cooldown = (self.cooldown - delta).max(0.0);
+        was_moving && !self.is_dashing()
+    }
+
+    /// The velocity of the dash in progress, covering `distance` in `duration`.
+    pub fn velocity(&self) -> Vec2 {
+        if self.config.duration > 0.0 {
+            self.direction * (self.config.distance / self.config.duration)
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    pub fn is_dashing(&self) -> bool {
+        self.moving > 0.0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown <= 0.0
+    }
+
+    /// How far the cooldown has recharged, from 0 (just dashed) to 1 (ready).
+    pub fn readiness(&self) -> f32 {
+        if self.config.cooldown > 0.0 {
+            1.0 - (self.cooldown / self.config.cooldown).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Cancels the dash and its cooldown, e.g. for a new run.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Powers of two, so the countdowns hit zero exactly.
+    const CONFIG: DashConfig = DashConfig {
+        distance: 160.0,
+        duration: 0.25,
+        invulnerability: 0.5,
+        cooldown: 1.0,
+    };
+    const DELTA: f32 = 0.0625;
+
+    #[test]
+    fn the_cooldown_gates_the_next_dash() {
+        let mut dash = Dash::new(CONFIG);
+        assert!(dash.try_start(Vec2::new(1.0, 0.0)));
+        assert!(!dash.try_start(Vec2::new(1.0, 0.0)));
+
+        dash.step(0.75);
+        assert!(!dash.is_ready());
+        assert_eq!(dash.readiness(), 0.75);
+        assert!(!dash.try_start(Vec2::new(0.0, 1.0)));
+
+        dash.step(0.25);
+        assert_eq!(dash.readiness(), 1.0);
+        assert!(dash.try_start(Vec2::new(0.0, 1.0)));
+        assert_eq!(dash.readiness(), 0.0);
+    }
+
+    #[test]
+    fn a_zero_direction_does_not_dash() {
+        let mut dash = Dash::new(CONFIG);
+        assert!(!dash.try_start(Vec2::ZERO));
+        assert!(!dash.is_dashing());
+        assert!(dash.is_ready());
+    }
+
+    #[test]
+    fn invulnerability_outlasts_the_movement() {
+        let mut dash = Dash::new(CONFIG);
+        dash.try_start(Vec2::new(0.0, -1.0));
+
+        let mut ends = 0;
+        for _ in 0..4 {
+            assert!(dash.is_dashing() && dash.is_invulnerable());
+            ends += dash.step(DELTA) as u32;
+        }
+        assert_eq!(ends, 1);
+        assert!(!dash.is_dashing());
+        assert!(dash.is_invulnerable());
+
+        dash.step(0.25);
+        assert!(!dash.is_invulnerable());
+        assert!(!dash.step(DELTA));
+    }
+
+    #[test]
+    fn the_velocity_covers_the_distance_in_the_duration() {
+        let mut dash = Dash::new(CONFIG);
+        dash.try_start(Vec2::new(3.0, 4.0));
+
+        let mut position = Vec2::ZERO;
+        while dash.is_dashing() {
+            position += dash.velocity() * DELTA;
+            dash.step(DELTA);
+        }
+        assert!((position.length() - CONFIG.distance).abs() < 1e-3, "{position:?}");
+        assert!((position.x / position.y - 0.75).abs() < 1e-5, "{position:?}");
+    }
+
+    #[test]
+    fn reset_keeps_the_config() {
+        let mut dash = Dash::new(CONFIG);
+        dash.try_start(Vec2::new(1.0, 0.0));
+        dash.reset();
+        assert_eq!(dash, Dash::new(CONFIG));
+        assert!(dash.is_ready());
+    }
+}
@@ -0,0 +1,83 @@
+/// Seconds between the fatal hit and the game over screen, in real time.
+pub const DEATH_DURATION: f32 = 1.5;
+
+/// How fast the game runs while the player is dying, if slow motion is enabled.
+pub const DEATH_TIME_SCALE: f32 = 0.3;
+
+/// Where the player is in the death sequence.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DeathPhase {
+    #[default]
+    Alive,
+    /// The death animation is playing; input is ignored.
+    Dying { elapsed: f32 },
+    /// The sequence is over and the player is gone.
+    Dead,
+}
+
+/// The alive → dying → dead sequence after the last life is lost.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeathSequence {
+    phase: DeathPhase,
+    /// Length of the dying phase in seconds.
+    pub duration: f32,
+}
+
+impl Default for DeathSequence {
+    fn default() -> Self {
+        Self {
+            phase: DeathPhase::Alive,
+            duration: DEATH_DURATION,
+        }
+    }
+}
+
+impl DeathSequence {
+    pub fn phase(&self) -> DeathPhase {
+        self.phase
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.phase == DeathPhase::Alive
+    }
+
+    /// Starts dying. Returns `false` if the player was not alive.
+    pub fn kill(&mut self) -> bool {
+        if !self.is_alive() {
+            return false;
+        }
+        self.phase = DeathPhase::Dying { elapsed: 0.0 };
+        true
+    }
+
+    /// Advances the dying phase by `delta` seconds.
+    ///
+    /// Returns `true` on the step the player becomes dead.
+    pub fn step(&mut self, delta: f32) -> bool {
+        let DeathPhase::Dying { elapsed } = self.phase else {
+            return false;
+        };
+
+        let elapsed = elapsed + delta;
+        if elapsed >= self.duration {
+            self.phase = DeathPhase::Dead;
+            return true;
+        }
+        self.phase = DeathPhase::Dying { elapsed };
+        false
+    }
+
+    /// How far through the dying phase the player is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        match self.phase {
+            DeathPhase::Alive => 0.0,
+            DeathPhase::Dying { elapsed } if self.duration > 0.0 => (elapsed / self.duration).min(1.0),
+            DeathPhase::Dying { .. } | DeathPhase::Dead => 1.0,
+        }
+    }
+
+    /// Brings the player back to life for a new run.
+    pub fn revive(&mut self) {
+        self.phase = DeathPhase::Alive;
+    }
+}
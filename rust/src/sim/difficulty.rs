@@ -0,0 +1,245 @@
+use super::spawn::SpawnParams;
+
+use std::f32::consts::PI;
+use std::fmt;
+
+/// How hard the game is at one point of a run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Difficulty {
+    /// Seconds between mob spawns.
+    pub mob_interval: f32,
+    /// Multiplier applied to the mobs' base speed range.
+    pub speed_scale: f32,
+    /// Maximum deviation in radians from pointing straight into the arena.
+    pub heading_spread: f32,
+}
+
+impl Difficulty {
+    /// Scales the base spawn parameters (the `Mob` speed range) to this difficulty.
+    pub fn apply(&self, base: &SpawnParams) -> SpawnParams {
+        SpawnParams {
+            min_speed: base.min_speed * self.speed_scale,
+            max_speed: base.max_speed * self.speed_scale,
+            heading_spread: self.heading_spread,
+        }
+    }
+
+    fn lerp(&self, to: &Difficulty, weight: f32) -> Difficulty {
+        let lerp = |from: f32, to: f32| from + (to - from) * weight;
+        Difficulty {
+            mob_interval: lerp(self.mob_interval, to.mob_interval),
+            speed_scale: lerp(self.speed_scale, to.speed_scale),
+            heading_spread: lerp(self.heading_spread, to.heading_spread),
+        }
+    }
+}
+
+/// A difficulty curve: the difficulty at given scores, linearly interpolated in between.
+///
+/// Before the first point the first difficulty applies, after the last point the last one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DifficultyCurve {
+    points: Vec<(i32, Difficulty)>,
+}
+
+impl DifficultyCurve {
+    /// Builds a curve from `(score, difficulty)` points, in any order.
+    pub fn new(mut points: Vec<(i32, Difficulty)>) -> Self {
+        points.sort_by_key(|&(score, _)| score);
+        Self { points }
+    }
+
+    /// The difficulty at `score`.
+    pub fn at(&self, score: i32) -> Difficulty {
+        let Some(&(_, first)) = self.points.first() else {
+            return DifficultyPreset::Normal.curve().at(score);
+        };
+
+        // Index of the first point past the score.
+        let next = self.points.partition_point(|&(point, _)| point <= score);
+
+        match (next.checked_sub(1).map(|i| self.points[i]), self.points.get(next)) {
+            (Some((from_score, from)), Some(&(to_score, to))) => {
+                let weight = (score - from_score) as f32 / (to_score - from_score) as f32;
+                from.lerp(&to, weight)
+            }
+            (Some((_, last)), None) => last,
+            (None, _) => first,
+        }
+    }
+}
+
+/// The named difficulty settings offered in the HUD.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl DifficultyPreset {
+    /// All presets, in the order they are listed in the HUD.
+    pub const ALL: [DifficultyPreset; 4] = [
+        DifficultyPreset::Easy,
+        DifficultyPreset::Normal,
+        DifficultyPreset::Hard,
+        DifficultyPreset::Nightmare,
+    ];
+
+    /// The preset at `index` in `ALL`, falling back to `Normal`.
+    pub fn from_index(index: i64) -> Self {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Self::ALL.get(index).copied())
+            .unwrap_or_default()
+    }
+
+    pub fn index(self) -> i64 {
+        Self::ALL.iter().position(|&preset| preset == self).unwrap_or(0) as i64
+    }
+
+    /// The difficulty curve of the preset.
+    ///
+    /// `Normal` starts at the game's original values (a mob every 0.5 seconds,
+    /// 300-600 speed, PI/4 spread) and gets steadily harder over the first two minutes.
+    pub fn curve(self) -> DifficultyCurve {
+        let point = |score, mob_interval, speed_scale, heading_spread| {
+            (
+                score,
+                Difficulty {
+                    mob_interval,
+                    speed_scale,
+                    heading_spread,
+                },
+            )
+        };
+
+        DifficultyCurve::new(match self {
+            DifficultyPreset::Easy => vec![
+                point(0, 0.8, 0.7, PI / 8.0),
+                point(60, 0.6, 0.9, PI / 6.0),
+                point(180, 0.45, 1.1, PI / 4.0),
+            ],
+            DifficultyPreset::Normal => vec![
+                point(0, 0.5, 1.0, PI / 4.0),
+                point(30, 0.4, 1.15, PI / 4.0),
+                point(120, 0.25, 1.5, PI / 3.0),
+            ],
+            DifficultyPreset::Hard => vec![
+                point(0, 0.4, 1.2, PI / 4.0),
+                point(30, 0.3, 1.4, PI / 3.0),
+                point(120, 0.18, 1.8, 5.0 * PI / 12.0),
+            ],
+            DifficultyPreset::Nightmare => vec![
+                point(0, 0.25, 1.5, PI / 3.0),
+                point(60, 0.15, 2.0, 5.0 * PI / 12.0),
+                point(180, 0.1, 2.5, PI / 2.0),
+            ],
+        })
+    }
+}
+
+impl fmt::Display for DifficultyPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DifficultyPreset::Easy => "Easy",
+            DifficultyPreset::Normal => "Normal",
+            DifficultyPreset::Hard => "Hard",
+            DifficultyPreset::Nightmare => "Nightmare",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn difficulty(mob_interval: f32, speed_scale: f32, heading_spread: f32) -> Difficulty {
+        Difficulty {
+            mob_interval,
+            speed_scale,
+            heading_spread,
+        }
+    }
+
+    fn curve() -> DifficultyCurve {
+        // Out of order on purpose.
+        DifficultyCurve::new(vec![(30, difficulty(0.2, 2.0, 1.0)), (10, difficulty(0.6, 1.0, 0.5))])
+    }
+
+    fn assert_close(actual: Difficulty, expected: Difficulty) {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+        assert!(
+            close(actual.mob_interval, expected.mob_interval)
+                && close(actual.speed_scale, expected.speed_scale)
+                && close(actual.heading_spread, expected.heading_spread),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn points_apply_at_their_score() {
+        assert_eq!(curve().at(10), difficulty(0.6, 1.0, 0.5));
+        assert_eq!(curve().at(30), difficulty(0.2, 2.0, 1.0));
+    }
+
+    #[test]
+    fn scores_between_points_interpolate() {
+        assert_close(curve().at(20), difficulty(0.4, 1.5, 0.75));
+        assert_close(curve().at(25), difficulty(0.3, 1.75, 0.875));
+    }
+
+    #[test]
+    fn the_ends_hold_outside_the_points() {
+        assert_eq!(curve().at(-5), curve().at(10));
+        assert_eq!(curve().at(0), curve().at(10));
+        assert_eq!(curve().at(31), curve().at(30));
+        assert_eq!(curve().at(i32::MAX), curve().at(30));
+    }
+
+    #[test]
+    fn an_empty_curve_is_normal() {
+        let empty = DifficultyCurve::new(Vec::new());
+        for score in [0, 45, 500] {
+            assert_eq!(empty.at(score), DifficultyPreset::Normal.curve().at(score));
+        }
+    }
+
+    #[test]
+    fn presets_only_get_harder() {
+        for score in 0..=300 {
+            let levels = DifficultyPreset::ALL.map(|preset| preset.curve().at(score));
+            for pair in levels.windows(2) {
+                let (easier, harder) = (pair[0], pair[1]);
+                assert!(harder.mob_interval <= easier.mob_interval, "score {score}");
+                assert!(harder.speed_scale >= easier.speed_scale, "score {score}");
+                assert!(harder.heading_spread >= easier.heading_spread, "score {score}");
+            }
+        }
+    }
+
+    #[test]
+    fn presets_get_harder_as_the_score_rises() {
+        for preset in DifficultyPreset::ALL {
+            let curve = preset.curve();
+            for score in 0..300 {
+                let (now, next) = (curve.at(score), curve.at(score + 1));
+                assert!(next.mob_interval <= now.mob_interval, "{preset} at {score}");
+                assert!(next.speed_scale >= now.speed_scale, "{preset} at {score}");
+                assert!(next.heading_spread >= now.heading_spread, "{preset} at {score}");
+            }
+        }
+    }
+
+    #[test]
+    fn presets_round_trip_through_their_index() {
+        for preset in DifficultyPreset::ALL {
+            assert_eq!(DifficultyPreset::from_index(preset.index()), preset);
+        }
+        assert_eq!(DifficultyPreset::from_index(-1), DifficultyPreset::Normal);
+        assert_eq!(DifficultyPreset::from_index(4), DifficultyPreset::Normal);
+    }
+}
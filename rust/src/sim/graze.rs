@@ -0,0 +1,148 @@
+/// Points for a graze before the combo multiplier.
+pub const GRAZE_POINTS: i32 = 2;
+
+/// How much every graze raises the combo multiplier.
+pub const MULTIPLIER_STEP: f32 = 0.5;
+
+/// The combo multiplier never goes above this.
+pub const MAX_MULTIPLIER: f32 = 5.0;
+
+/// Seconds after the last graze before the multiplier starts to decay.
+pub const DECAY_DELAY: f32 = 1.5;
+
+/// How fast the multiplier falls back to 1, per second.
+pub const DECAY_RATE: f32 = 1.0;
+
+/// The combo of mobs passing close to the player without hitting them.
+///
+/// Every graze is worth `GRAZE_POINTS` times the multiplier and raises the multiplier.
+/// Without new grazes the multiplier decays back to 1, which ends the combo.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GrazeCombo {
+    // Grazes since the combo started.
+    count: u32,
+    multiplier: f32,
+    // Seconds since the last graze.
+    idle: f32,
+}
+
+impl Default for GrazeCombo {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            multiplier: 1.0,
+            idle: 0.0,
+        }
+    }
+}
+
+impl GrazeCombo {
+    /// Registers a graze and returns the points it is worth.
+    pub fn graze(&mut self) -> i32 {
+        let points = (GRAZE_POINTS as f32 * self.multiplier).round() as i32;
+        self.count += 1;
+        self.multiplier = (self.multiplier + MULTIPLIER_STEP).min(MAX_MULTIPLIER);
+        self.idle = 0.0;
+        points
+    }
+
+    /// Decays the multiplier once `DECAY_DELAY` has passed without a graze.
+    ///
+    /// Returns `true` on the step the combo ends.
+    pub fn step(&mut self, delta: f32) -> bool {
+        if self.count == 0 {
+            return false;
+        }
+
+        self.idle += delta;
+        if self.idle > DECAY_DELAY {
+            self.multiplier = (self.multiplier - DECAY_RATE * delta).max(1.0);
+        }
+
+        if self.multiplier <= 1.0 {
+            self.count = 0;
+            return true;
+        }
+        false
+    }
+
+    /// Grazes in the current combo; 0 when there is none.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn multiplier(&self) -> f32 {
+        self.multiplier
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.count > 0
+    }
+
+    /// Ends the combo, e.g. when the player is hit or a new run starts.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Exact in binary, so the idle time adds up to the decay delay exactly.
+    const DELTA: f32 = 0.25;
+
+    #[test]
+    fn every_graze_raises_the_multiplier_up_to_the_cap() {
+        let mut combo = GrazeCombo::default();
+        let points: Vec<i32> = (0..10).map(|_| combo.graze()).collect();
+        assert_eq!(points, [2, 3, 4, 5, 6, 7, 8, 9, 10, 10]);
+        assert_eq!(combo.multiplier(), MAX_MULTIPLIER);
+        assert_eq!(combo.count(), 10);
+    }
+
+    #[test]
+    fn the_multiplier_holds_until_the_decay_delay() {
+        let mut combo = GrazeCombo::default();
+        combo.graze();
+        combo.graze();
+
+        for _ in 0..(DECAY_DELAY / DELTA) as u32 {
+            assert!(!combo.step(DELTA));
+        }
+        assert_eq!(combo.multiplier(), 2.0);
+
+        combo.step(DELTA);
+        assert_eq!(combo.multiplier(), 2.0 - DECAY_RATE * DELTA);
+
+        // A graze restarts the delay.
+        combo.graze();
+        combo.step(DECAY_DELAY);
+        assert_eq!(combo.multiplier(), 2.25);
+    }
+
+    #[test]
+    fn the_end_of_a_combo_is_reported_once() {
+        let mut combo = GrazeCombo::default();
+        assert!(!combo.step(DELTA));
+
+        combo.graze();
+        let ends = (0..40).filter(|_| combo.step(DELTA)).count();
+        assert_eq!(ends, 1);
+        assert!(!combo.is_active());
+        assert_eq!(combo.count(), 0);
+        assert_eq!(combo.multiplier(), 1.0);
+
+        // The next graze starts from scratch.
+        assert_eq!(combo.graze(), GRAZE_POINTS);
+    }
+
+    #[test]
+    fn reset_ends_the_combo_silently() {
+        let mut combo = GrazeCombo::default();
+        combo.graze();
+        combo.reset();
+        assert_eq!(combo, GrazeCombo::default());
+        assert!(!combo.step(10.0));
+    }
+}
@@ -0,0 +1,328 @@
+use super::bytes::{checksum, ByteReader, Truncated};
+
+use std::fmt;
+
+/// Identifies a high score file.
+const MAGIC: &[u8; 4] = b"DGHS";
+
+/// Version of the layout written by `HighScoreTable::encode`.
+///
+/// - 1: name and score per entry.
+/// - 2: adds the seed of the run so a record can be replayed.
+const VERSION: u8 = 2;
+
+/// How many entries the table keeps by default.
+pub const DEFAULT_CAPACITY: usize = 10;
+
+/// Longest name, in characters, that can be entered for a record.
+pub const MAX_NAME_LENGTH: usize = 12;
+
+/// Name used until the player types one in.
+pub const DEFAULT_NAME: &str = "Player";
+
+/// One row of the high score table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: i32,
+    /// Seed of the run, 0 if unknown (records migrated from version 1).
+    pub seed: u64,
+}
+
+/// Why a high score file could not be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HighScoreError {
+    /// The data does not start with the high score magic bytes.
+    NotAHighScoreFile,
+    /// The file was written by a newer version of the game.
+    UnsupportedVersion(u8),
+    /// The data ends in the middle of a field.
+    Truncated,
+    /// The stored checksum does not match the data.
+    ChecksumMismatch,
+    /// A name is not valid UTF-8.
+    InvalidName,
+}
+
+impl fmt::Display for HighScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HighScoreError::NotAHighScoreFile => write!(f, "not a high score file"),
+            HighScoreError::UnsupportedVersion(version) => write!(f, "unsupported high score version {version}"),
+            HighScoreError::Truncated => write!(f, "high score file is truncated"),
+            HighScoreError::ChecksumMismatch => write!(f, "high score file is corrupted"),
+            HighScoreError::InvalidName => write!(f, "high score file contains an invalid name"),
+        }
+    }
+}
+
+impl From<Truncated> for HighScoreError {
+    fn from(_: Truncated) -> Self {
+        HighScoreError::Truncated
+    }
+}
+
+/// The best scores, highest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighScoreTable {
+    entries: Vec<HighScore>,
+    capacity: usize,
+}
+
+impl Default for HighScoreTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl HighScoreTable {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    /// Whether `score` would make it onto the table.
+    pub fn qualifies(&self, score: i32) -> bool {
+        score > 0
+            && (self.entries.len() < self.capacity
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds an entry and returns its rank (0 is the best), or `None` if it did not make the cut.
+    ///
+    /// Ties go below the existing entries, so an older record keeps its place.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+
+        let rank = self.entries.partition_point(|existing| existing.score >= entry.score);
+        self.entries.insert(rank, entry);
+        self.entries.truncate(self.capacity);
+        Some(rank)
+    }
+
+    /// Changes the name of the entry at `rank`.
+    pub fn rename(&mut self, rank: usize, name: &str) {
+        if let Some(entry) = self.entries.get_mut(rank) {
+            entry.name = sanitize_name(name);
+        }
+    }
+
+    /// Serializes the table in the current format.
+    ///
+    /// Layout (little endian): magic, version byte, entry count (u8), then per entry
+    /// the score (i32), seed (u64), name length (u8) and UTF-8 name, followed by a
+    /// checksum (u32) of everything before it.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.entries.len() as u8);
+
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.score.to_le_bytes());
+            bytes.extend_from_slice(&entry.seed.to_le_bytes());
+            bytes.push(entry.name.len() as u8);
+            bytes.extend_from_slice(entry.name.as_bytes());
+        }
+
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+        bytes
+    }
+
+    /// Reads a table written by this or any earlier version of the game.
+    ///
+    /// Older versions are migrated to the current layout; the caller should save
+    /// the table again to upgrade the file on disk.
+    pub fn decode(bytes: &[u8], capacity: usize) -> Result<Self, HighScoreError> {
+        // Verify the checksum before looking at anything else,
+        // so a truncated or corrupted file is rejected as a whole.
+        if bytes.len() < MAGIC.len() + 4 {
+            return Err(HighScoreError::Truncated);
+        }
+        let (data, stored) = bytes.split_at(bytes.len() - 4);
+
+        let mut reader = ByteReader::new(data);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(HighScoreError::NotAHighScoreFile);
+        }
+        if ByteReader::new(stored).u32()? != checksum(data) {
+            return Err(HighScoreError::ChecksumMismatch);
+        }
+
+        let version = reader.u8()?;
+        if version == 0 || version > VERSION {
+            return Err(HighScoreError::UnsupportedVersion(version));
+        }
+
+        let count = reader.u8()?;
+        let mut table = Self::new(capacity);
+
+        for _ in 0..count {
+            let score = reader.i32()?;
+            // Version 1 did not store seeds.
+            let seed = if version >= 2 { reader.u64()? } else { 0 };
+            let name_length = reader.u8()? as usize;
+            let name = std::str::from_utf8(reader.take(name_length)?)
+                .map_err(|_| HighScoreError::InvalidName)?;
+
+            // Going through `insert` keeps the table sorted and within capacity
+            // even if the file was edited by hand.
+            table.insert(HighScore {
+                name: sanitize_name(name),
+                score,
+                seed,
+            });
+        }
+
+        Ok(table)
+    }
+}
+
+/// Trims a typed name and limits it to `MAX_NAME_LENGTH` characters.
+///
+/// An empty name falls back to `DEFAULT_NAME`.
+pub fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LENGTH)
+        .collect();
+
+    match name.trim_end() {
+        "" => DEFAULT_NAME.to_string(),
+        name => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: i32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            seed: score as u64 * 7,
+        }
+    }
+
+    fn table() -> HighScoreTable {
+        let mut table = HighScoreTable::new(3);
+        table.insert(entry("Ada", 30));
+        table.insert(entry("Bob", 10));
+        table.insert(entry("Cy", 20));
+        table
+    }
+
+    #[test]
+    fn insert_keeps_the_best_first() {
+        let table = table();
+        let scores: Vec<i32> = table.entries().iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, [30, 20, 10]);
+    }
+
+    #[test]
+    fn ties_go_below_older_records() {
+        let mut table = table();
+        assert_eq!(table.insert(entry("Dee", 20)), Some(2));
+        assert_eq!(table.entries()[1].name, "Cy");
+        assert_eq!(table.entries()[2].name, "Dee");
+    }
+
+    #[test]
+    fn insert_stops_at_the_capacity() {
+        let mut table = table();
+        // Not better than the last entry of a full table.
+        assert_eq!(table.insert(entry("Eve", 10)), None);
+        assert_eq!(table.insert(entry("Eve", 5)), None);
+        // Better than the last one, which drops off.
+        assert_eq!(table.insert(entry("Eve", 40)), Some(0));
+        assert_eq!(table.entries().len(), 3);
+        assert!(table.entries().iter().all(|entry| entry.name != "Bob"));
+        // A score of zero never counts.
+        assert_eq!(HighScoreTable::new(3).insert(entry("Zed", 0)), None);
+    }
+
+    #[test]
+    fn encoding_round_trips() {
+        let mut table = table();
+        table.rename(0, "  Zoë the Great and Terrible ");
+        let decoded = HighScoreTable::decode(&table.encode(), 3).unwrap();
+        assert_eq!(decoded, table);
+        assert_eq!(decoded.entries()[0].name, "Zoë the Grea");
+    }
+
+    #[test]
+    fn a_flipped_byte_fails_the_checksum() {
+        let bytes = table().encode();
+        for index in MAGIC.len()..bytes.len() {
+            let mut corrupted = bytes.clone();
+            corrupted[index] ^= 0x10;
+            assert_eq!(
+                HighScoreTable::decode(&corrupted, 3),
+                Err(HighScoreError::ChecksumMismatch),
+                "byte {index}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_truncation_is_an_error() {
+        let bytes = table().encode();
+        for length in 0..bytes.len() {
+            assert!(HighScoreTable::decode(&bytes[..length], 3).is_err(), "length {length}");
+        }
+    }
+
+    #[test]
+    fn version_1_tables_migrate_without_seeds() {
+        // Version 1: magic, version, count, then score and name per entry, and the checksum.
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(1);
+        bytes.push(2);
+        for (name, score) in [("Bob", 10), ("Ada", 30)] {
+            bytes.extend_from_slice(&i32::to_le_bytes(score));
+            bytes.push(name.len() as u8);
+            bytes.extend_from_slice(name.as_bytes());
+        }
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+
+        let table = HighScoreTable::decode(&bytes, DEFAULT_CAPACITY).unwrap();
+        let expected = [("Ada", 30), ("Bob", 10)].map(|(name, score)| HighScore {
+            name: name.to_string(),
+            score,
+            seed: 0,
+        });
+        assert_eq!(table.entries(), expected);
+
+        // Saved again, it is in the current version.
+        let upgraded = table.encode();
+        assert_eq!(upgraded[MAGIC.len()], VERSION);
+        assert_eq!(HighScoreTable::decode(&upgraded, DEFAULT_CAPACITY), Ok(table));
+    }
+
+    #[test]
+    fn foreign_and_future_files_are_rejected() {
+        let mut bytes = b"XXXX".to_vec();
+        bytes.extend_from_slice(&[2, 0]);
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+        assert_eq!(HighScoreTable::decode(&bytes, 3), Err(HighScoreError::NotAHighScoreFile));
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[VERSION + 1, 0]);
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+        assert_eq!(
+            HighScoreTable::decode(&bytes, 3),
+            Err(HighScoreError::UnsupportedVersion(VERSION + 1))
+        );
+    }
+}
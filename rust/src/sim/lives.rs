@@ -0,0 +1,155 @@
+/// Lives a run starts with unless configured otherwise.
+pub const DEFAULT_LIVES: u32 = 3;
+
+/// Seconds the player cannot be hit after losing a life.
+pub const INVULNERABILITY_TIME: f32 = 2.0;
+
+/// How often the player blinks while invulnerable, in blinks per second.
+const BLINK_RATE: f32 = 8.0;
+
+/// What a hit did to the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitOutcome {
+    /// The player was invulnerable and nothing happened.
+    Ignored,
+    /// A life was lost, `remaining` are left and invulnerability has started.
+    LifeLost { remaining: u32 },
+    /// The last life was lost; the run is over.
+    OutOfLives,
+}
+
+/// The player's lives and the invulnerability window after losing one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lives {
+    remaining: u32,
+    // Seconds of invulnerability left.
+    invulnerable: f32,
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIVES)
+    }
+}
+
+impl Lives {
+    /// Starts with `count` lives; at least one.
+    pub fn new(count: u32) -> Self {
+        Self {
+            remaining: count.max(1),
+            invulnerable: 0.0,
+        }
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+
+    /// Takes a life, unless the player is invulnerable.
+    pub fn hit(&mut self) -> HitOutcome {
+        if self.is_invulnerable() || self.remaining == 0 {
+            return HitOutcome::Ignored;
+        }
+
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            return HitOutcome::OutOfLives;
+        }
+
+        self.invulnerable = INVULNERABILITY_TIME;
+        HitOutcome::LifeLost {
+            remaining: self.remaining,
+        }
+    }
+
+    /// Counts the invulnerability down by `delta` seconds.
+    ///
+    /// Returns `true` on the step it runs out.
+    pub fn step(&mut self, delta: f32) -> bool {
+        if !self.is_invulnerable() {
+            return false;
+        }
+
+        self.invulnerable -= delta;
+        if self.invulnerable <= 0.0 {
+            self.invulnerable = 0.0;
+            return true;
+        }
+        false
+    }
+
+    /// Whether the blinking sprite is shown at the moment; always `true` when not invulnerable.
+    pub fn blink_visible(&self) -> bool {
+        !self.is_invulnerable() || ((self.invulnerable * BLINK_RATE) as u32) % 2 == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_hit_costs_a_life_and_starts_invulnerability() {
+        let mut lives = Lives::new(3);
+        assert_eq!(lives.hit(), HitOutcome::LifeLost { remaining: 2 });
+        assert_eq!(lives.remaining(), 2);
+        assert!(lives.is_invulnerable());
+    }
+
+    #[test]
+    fn hits_are_ignored_while_invulnerable() {
+        let mut lives = Lives::new(3);
+        lives.hit();
+        assert_eq!(lives.hit(), HitOutcome::Ignored);
+        lives.step(INVULNERABILITY_TIME / 2.0);
+        assert_eq!(lives.hit(), HitOutcome::Ignored);
+        assert_eq!(lives.remaining(), 2);
+    }
+
+    #[test]
+    fn the_last_life_ends_the_run() {
+        let mut lives = Lives::new(1);
+        assert_eq!(lives.hit(), HitOutcome::OutOfLives);
+        assert_eq!(lives.remaining(), 0);
+        // Nothing left to lose.
+        assert_eq!(lives.hit(), HitOutcome::Ignored);
+        assert_eq!(Lives::new(0).remaining(), 1);
+    }
+
+    #[test]
+    fn invulnerability_ends_on_exactly_one_step() {
+        let mut lives = Lives::new(3);
+        assert!(!lives.step(1.0));
+
+        lives.hit();
+        assert!(!lives.step(INVULNERABILITY_TIME - 0.5));
+        assert!(lives.step(1.0));
+        assert!(!lives.is_invulnerable());
+        assert!(!lives.step(1.0));
+
+        // The next hit counts again.
+        assert_eq!(lives.hit(), HitOutcome::LifeLost { remaining: 1 });
+    }
+
+    #[test]
+    fn the_sprite_blinks_only_while_invulnerable() {
+        let mut lives = Lives::new(3);
+        assert!(lives.blink_visible());
+
+        lives.hit();
+        let delta = 1.0 / BLINK_RATE;
+        let mut shown = Vec::new();
+        for _ in 0..4 {
+            shown.push(lives.blink_visible());
+            lives.step(delta);
+        }
+        assert!(shown.contains(&true) && shown.contains(&false), "{shown:?}");
+
+        lives.step(INVULNERABILITY_TIME);
+        assert!(lives.blink_visible());
+    }
+}
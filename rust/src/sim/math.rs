@@ -0,0 +1,138 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+/// A plain 2D vector used by the simulation.
+///
+/// It mirrors the handful of `Vector2` operations the game needs so the
+/// simulation does not have to link against Godot to do its maths.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Builds a unit vector pointing at `angle` radians (0 is +X, PI/2 is +Y).
+    pub fn from_angle(angle: f32) -> Self {
+        Self::new(angle.cos(), angle.sin())
+    }
+
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn length_squared(self) -> f32 {
+        self.x * self.x + self.y * self.y
+    }
+
+    /// Returns the vector scaled to a length of 1, or zero if it has no length.
+    pub fn normalized(self) -> Self {
+        let length = self.length();
+        if length > 0.0 {
+            self * (1.0 / length)
+        } else {
+            Self::ZERO
+        }
+    }
+
+    /// Rotates the vector by `angle` radians, like `Vector2::rotated`.
+    pub fn rotated(self, angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// The angle of the vector in radians, like `Vector2::angle`.
+    pub fn angle(self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    pub fn distance_to(self, other: Vec2) -> f32 {
+        (other - self).length()
+    }
+
+    pub fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Linearly interpolates between `self` and `to` by `weight` (0..=1).
+    pub fn lerp(self, to: Vec2, weight: f32) -> Self {
+        self + (to - self) * weight
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Vec2) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Vec2 {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance_to(b) < 1e-5
+    }
+
+    #[test]
+    fn rotating_follows_the_y_down_convention() {
+        // A quarter turn takes +X to +Y, which is down the screen in Godot.
+        assert!(close(Vec2::new(1.0, 0.0).rotated(PI / 2.0), Vec2::new(0.0, 1.0)));
+        assert!(close(Vec2::from_angle(PI), Vec2::new(-1.0, 0.0)));
+        assert!((Vec2::new(0.0, 2.0).angle() - PI / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn normalizing_zero_stays_zero() {
+        assert_eq!(Vec2::ZERO.normalized(), Vec2::ZERO);
+        assert!((Vec2::new(3.0, 4.0).normalized().length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lerp_goes_between_the_ends() {
+        let a = Vec2::new(0.0, 10.0);
+        let b = Vec2::new(10.0, 0.0);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert!(close(a.lerp(b, 0.5), Vec2::new(5.0, 5.0)));
+    }
+}
@@ -0,0 +1,276 @@
+use super::config::{self, ConfigError, Section};
+use super::powerup::PowerUpKind;
+
+/// When a milestone is reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MilestoneTrigger {
+    /// Once, at this score.
+    At(i32),
+    /// At `start` and then every `interval` points after it.
+    Every { interval: i32, start: i32 },
+}
+
+impl MilestoneTrigger {
+    /// The scores in `previous + 1..=score` that trigger the milestone, in order.
+    pub fn scores_between(self, previous: i32, score: i32) -> Vec<i32> {
+        match self {
+            MilestoneTrigger::At(at) if previous < at && at <= score => vec![at],
+            MilestoneTrigger::At(_) => Vec::new(),
+            MilestoneTrigger::Every { interval, start } => {
+                let first = start.max(previous + 1);
+                // Round up to the next score on the grid.
+                let steps = (first - start + interval - 1) / interval;
+                (start + steps * interval..=score)
+                    .step_by(interval as usize)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Something extra a milestone gives the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MilestoneReward {
+    /// Starts a power-up, as if its pickup had been collected.
+    PowerUp(PowerUpKind),
+    /// Adds bonus points.
+    Points(i32),
+}
+
+/// A score milestone, as described in `milestones.toml`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Milestone {
+    /// The section name in the file, e.g. "score50".
+    pub name: String,
+    pub trigger: MilestoneTrigger,
+    /// Name of the sound group to play, if any.
+    pub sound: Option<String>,
+    /// Message to show on the HUD, if any. `{score}` is replaced with the score reached.
+    pub message: Option<String>,
+    /// Points the difficulty curve jumps ahead by, on top of the score.
+    pub difficulty_bump: i32,
+    pub reward: Option<MilestoneReward>,
+}
+
+impl Milestone {
+    /// A milestone that only plays a sound once, at `score`.
+    pub fn sound_at(score: i32, sound: &str) -> Self {
+        Self {
+            name: format!("score{score}"),
+            trigger: MilestoneTrigger::At(score),
+            sound: Some(sound.to_string()),
+            message: None,
+            difficulty_bump: 0,
+            reward: None,
+        }
+    }
+
+    /// Reads a milestone from a `[name]` section.
+    ///
+    /// The section needs either `at` or `every`; every other key is optional.
+    pub fn from_section(section: &Section) -> Result<Self, ConfigError> {
+        let error = |message: &str| ConfigError::new(0, format!("[{}] {message}", section.name));
+
+        let trigger = match (section.get("at"), section.get("every")) {
+            (Some(_), None) => MilestoneTrigger::At(section.number("at", 0.0)? as i32),
+            (None, Some(_)) => {
+                let interval = section.number("every", 0.0)? as i32;
+                let start = section.number("from", interval.into())? as i32;
+                if interval <= 0 {
+                    return Err(error("every must be positive"));
+                }
+                MilestoneTrigger::Every { interval, start }
+            }
+            _ => return Err(error("needs either 'at' or 'every'")),
+        };
+        let first = match trigger {
+            MilestoneTrigger::At(at) => at,
+            MilestoneTrigger::Every { start, .. } => start,
+        };
+        if first <= 0 {
+            return Err(error("must be reached at a positive score"));
+        }
+
+        let optional = |key: &str| -> Result<Option<String>, ConfigError> {
+            let value = section.string(key, "")?;
+            Ok((!value.is_empty()).then_some(value))
+        };
+
+        let reward = match (section.get("power_up"), section.get("bonus")) {
+            (None, None) => None,
+            (Some(_), None) => {
+                let name = section.string("power_up", "")?;
+                let kind = PowerUpKind::from_name(&name)
+                    .ok_or_else(|| error(&format!("unknown power_up \"{name}\"")))?;
+                Some(MilestoneReward::PowerUp(kind))
+            }
+            (None, Some(_)) => Some(MilestoneReward::Points(section.number("bonus", 0.0)? as i32)),
+            (Some(_), Some(_)) => return Err(error("can give either a power_up or a bonus, not both")),
+        };
+
+        let difficulty_bump = section.number("difficulty", 0.0)? as i32;
+        if difficulty_bump < 0 {
+            return Err(error("difficulty must not be negative"));
+        }
+
+        Ok(Self {
+            name: section.name.clone(),
+            trigger,
+            sound: optional("sound")?,
+            message: optional("message")?,
+            difficulty_bump,
+            reward,
+        })
+    }
+
+    /// The HUD message for reaching the milestone at `score`.
+    pub fn message_at(&self, score: i32) -> Option<String> {
+        self.message
+            .as_ref()
+            .map(|message| message.replace("{score}", &score.to_string()))
+    }
+}
+
+/// Every milestone of a run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MilestoneTable {
+    milestones: Vec<Milestone>,
+}
+
+impl Default for MilestoneTable {
+    /// The original milestones: a sound at 5, 10, 20, 25, 35 and 50 points.
+    fn default() -> Self {
+        Self::new(
+            [5, 10, 20, 25, 35, 50]
+                .into_iter()
+                .map(|score| Milestone::sound_at(score, &format!("score{score}")))
+                .collect(),
+        )
+    }
+}
+
+impl MilestoneTable {
+    pub fn new(milestones: Vec<Milestone>) -> Self {
+        Self { milestones }
+    }
+
+    pub fn milestones(&self) -> &[Milestone] {
+        &self.milestones
+    }
+
+    /// The milestones reached going from `previous` to `score`, with the score
+    /// each was reached at, ordered by that score and then by file order.
+    ///
+    /// A score can jump over several milestones at once, so every score passed counts.
+    pub fn reached(&self, previous: i32, score: i32) -> Vec<(i32, &Milestone)> {
+        let mut reached: Vec<(i32, &Milestone)> = self
+            .milestones
+            .iter()
+            .flat_map(|milestone| {
+                milestone
+                    .trigger
+                    .scores_between(previous, score)
+                    .into_iter()
+                    .map(move |at| (at, milestone))
+            })
+            .collect();
+        // A stable sort keeps the file order among milestones reached at the same score.
+        reached.sort_by_key(|&(at, _)| at);
+        reached
+    }
+}
+
+/// Reads every milestone in a milestones file, one per section.
+pub fn parse_milestones(text: &str) -> Result<MilestoneTable, ConfigError> {
+    let milestones = config::parse(text)?
+        .iter()
+        .map(Milestone::from_section)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(MilestoneTable::new(milestones))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn at_triggers_once_when_passed() {
+        let trigger = MilestoneTrigger::At(10);
+        assert_eq!(trigger.scores_between(9, 10), [10]);
+        assert_eq!(trigger.scores_between(5, 20), [10]);
+        assert!(trigger.scores_between(10, 11).is_empty());
+        assert!(trigger.scores_between(8, 9).is_empty());
+    }
+
+    #[test]
+    fn every_starts_at_from_and_repeats() {
+        let trigger = MilestoneTrigger::Every { interval: 25, start: 10 };
+        assert!(trigger.scores_between(0, 9).is_empty());
+        assert_eq!(trigger.scores_between(9, 10), [10]);
+        assert!(trigger.scores_between(10, 34).is_empty());
+        assert_eq!(trigger.scores_between(34, 35), [35]);
+        // Starting between two repeats rounds up to the next one.
+        assert_eq!(trigger.scores_between(36, 60), [60]);
+    }
+
+    #[test]
+    fn one_tick_can_pass_several_repeats() {
+        let trigger = MilestoneTrigger::Every { interval: 5, start: 5 };
+        assert_eq!(trigger.scores_between(3, 21), [5, 10, 15, 20]);
+    }
+
+    #[test]
+    fn no_score_change_reaches_nothing() {
+        let table = MilestoneTable::default();
+        assert!(MilestoneTrigger::Every { interval: 5, start: 5 }.scores_between(10, 10).is_empty());
+        assert!(MilestoneTrigger::At(10).scores_between(10, 10).is_empty());
+        assert!(table.reached(10, 10).is_empty());
+    }
+
+    #[test]
+    fn reached_orders_by_score_then_file_order() {
+        let table = parse_milestones(
+            "[every10]\nevery = 10\n\n[at20]\nat = 20\nbonus = 5\n\n[at15]\nat = 15\nsound = \"ding\"\n",
+        )
+        .unwrap();
+
+        let reached: Vec<(i32, &str)> = table
+            .reached(5, 20)
+            .into_iter()
+            .map(|(at, milestone)| (at, milestone.name.as_str()))
+            .collect();
+        assert_eq!(reached, [(10, "every10"), (15, "at15"), (20, "every10"), (20, "at20")]);
+    }
+
+    #[test]
+    fn reads_every_key() {
+        let table = parse_milestones(
+            "[big]\nevery = 50\nfrom = 100\nmessage = \"{score}!\"\ndifficulty = 3\npower_up = \"shield\"\n",
+        )
+        .unwrap();
+        let milestone = &table.milestones()[0];
+        assert_eq!(milestone.trigger, MilestoneTrigger::Every { interval: 50, start: 100 });
+        assert_eq!(milestone.message_at(150).as_deref(), Some("150!"));
+        assert_eq!(milestone.difficulty_bump, 3);
+        assert_eq!(milestone.reward, Some(MilestoneReward::PowerUp(PowerUpKind::Shield)));
+        assert_eq!(milestone.sound, None);
+    }
+
+    #[test]
+    fn bad_milestones_are_rejected() {
+        for text in [
+            "[m]\nsound = \"ding\"\n",
+            "[m]\nat = 5\nevery = 5\n",
+            "[m]\nevery = 0\n",
+            "[m]\nevery = -5\n",
+            "[m]\nat = 0\n",
+            "[m]\nevery = 5\nfrom = -5\n",
+            "[m]\nat = 5\npower_up = \"laser\"\n",
+            "[m]\nat = 5\npower_up = \"shield\"\nbonus = 3\n",
+            "[m]\nat = 5\ndifficulty = -1\n",
+            "[m]\nat = \"five\"\n",
+        ] {
+            assert!(parse_milestones(text).is_err(), "{text:?}");
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Engine-independent game rules.
+//!
+//! Nothing in here touches Godot: the `GameScene`, `Player` and `Mob` classes
+//! feed engine state in and apply the results back to their nodes, which
+//! keeps the rules testable with a plain `cargo test`.
+
+pub mod archetype;
+pub mod arena;
+pub mod audio;
+pub mod behaviour;
+pub mod bindings;
+pub mod bytes;
+pub mod collision;
+pub mod config;
+pub mod dash;
+pub mod death;
+pub mod difficulty;
+pub mod graze;
+pub mod highscore;
+pub mod lives;
+pub mod math;
+pub mod milestone;
+pub mod music;
+pub mod player;
+pub mod player_state;
+pub mod powerup;
+pub mod replay;
+pub mod rng;
+pub mod score;
+pub mod settings;
+pub mod shake;
+pub mod spawn;
+pub mod world;
+
+pub use math::Vec2;
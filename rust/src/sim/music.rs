@@ -0,0 +1,99 @@
+use super::difficulty::Difficulty;
+
+/// How much faster the music plays at full intensity.
+pub const MAX_SPEEDUP: f32 = 0.15;
+
+/// Live mobs at which the mob count alone counts as full intensity.
+pub const BUSY_MOB_COUNT: f32 = 12.0;
+
+/// Mob pressure (spawns per second times the speed scale) of a calm game:
+/// the start of a run on "Normal".
+pub const CALM_PRESSURE: f32 = 2.0;
+
+/// Mob pressure at which the difficulty alone counts as full intensity.
+pub const INTENSE_PRESSURE: f32 = 10.0;
+
+/// How fast the intensity follows the danger, per second, so the tempo never jumps.
+pub const INTENSITY_RATE: f32 = 0.25;
+
+/// How dangerous the game is right now, from 0 (calm) to 1 (intense).
+///
+/// Half of it comes from the number of live mobs, half from how many and how fast
+/// mobs spawn at the current difficulty.
+pub fn danger(live_mobs: usize, difficulty: &Difficulty) -> f32 {
+    let crowd = (live_mobs as f32 / BUSY_MOB_COUNT).min(1.0);
+
+    let pressure = difficulty.speed_scale / difficulty.mob_interval.max(0.01);
+    let pressure = ((pressure - CALM_PRESSURE) / (INTENSE_PRESSURE - CALM_PRESSURE)).clamp(0.0, 1.0);
+
+    (crowd + pressure) / 2.0
+}
+
+/// The intensity of the music, easing towards the current danger.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MusicIntensity {
+    current: f32,
+    target: f32,
+}
+
+impl MusicIntensity {
+    /// Sets the intensity to ease towards, from 0 to 1.
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target.clamp(0.0, 1.0);
+    }
+
+    /// Moves the intensity towards the target by at most `INTENSITY_RATE * delta`.
+    pub fn step(&mut self, delta: f32) -> f32 {
+        let change = (self.target - self.current).clamp(-INTENSITY_RATE * delta, INTENSITY_RATE * delta);
+        self.current += change;
+        self.current
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current
+    }
+
+    /// The playback speed of the music at this intensity, 1 being normal speed.
+    pub fn playback_rate(&self) -> f32 {
+        1.0 + self.current * MAX_SPEEDUP
+    }
+}
+
+/// A crossfade from one track to another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crossfade {
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Crossfade {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advances the crossfade by `delta` seconds. Returns `true` once it is over.
+    pub fn step(&mut self, delta: f32) -> bool {
+        self.elapsed += delta;
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// The linear volumes of the track fading out and the one fading in.
+    ///
+    /// The curves keep the total power constant, so the mix does not dip in the middle.
+    pub fn volumes(&self) -> (f32, f32) {
+        let progress = if self.duration > 0.0 {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let angle = progress * std::f32::consts::FRAC_PI_2;
+        (angle.cos(), angle.sin())
+    }
+}
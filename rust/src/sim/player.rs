@@ -0,0 +1,276 @@
+use super::math::Vec2;
+
+/// Distance in pixels at which the player counts as having reached the pointer they follow.
+pub const POINTER_REACHED: f32 = 2.0;
+
+/// An analogue movement vector, quantized so that replays reproduce it exactly.
+///
+/// Each axis goes from -127 to 127, and the vector is never longer than 127.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stick {
+    pub x: i8,
+    pub y: i8,
+}
+
+impl Stick {
+    const SCALE: f32 = 127.0;
+
+    /// Quantizes a movement vector, shortening it to a length of 1 first if it is longer.
+    pub fn from_vector(vector: Vec2) -> Self {
+        let vector = if vector.length() > 1.0 { vector.normalized() } else { vector };
+        let quantize = |value: f32| (value * Self::SCALE).round().clamp(-Self::SCALE, Self::SCALE) as i8;
+        Self {
+            x: quantize(vector.x),
+            y: quantize(vector.y),
+        }
+    }
+
+    /// The movement vector, at most 1 long.
+    pub fn vector(self) -> Vec2 {
+        let vector = Vec2::new(f32::from(self.x), f32::from(self.y)) * (1.0 / Self::SCALE);
+        // Rounding both axes up can make a diagonal a hair longer than 1.
+        if vector.length() > 1.0 {
+            vector.normalized()
+        } else {
+            vector
+        }
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.x == 0 && self.y == 0
+    }
+
+    /// The stick that moves a player at `position` with `speed` towards `target`:
+    /// at full speed, but slowing down on the last frame so it stops on the target
+    /// instead of overshooting it `delta` seconds later.
+    pub fn toward(position: Vec2, target: Vec2, speed: f32, delta: f32) -> Self {
+        let offset = target - position;
+        let distance = offset.length();
+        if distance <= POINTER_REACHED || speed <= 0.0 || delta <= 0.0 {
+            return Self::default();
+        }
+
+        let strength = (distance / (speed * delta)).min(1.0);
+        Self::from_vector(offset.normalized() * strength)
+    }
+}
+
+/// The state of the four movement actions for one physics frame,
+/// and whether the dash action was pressed on that frame.
+///
+/// `stick` is the analogue movement read from the same actions, which lets a gamepad
+/// stick or the on-screen joystick move the player slower than full speed.
+/// Replays recorded before it existed leave it at zero and move with the four actions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MoveInput {
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    pub dash: bool,
+    pub stick: Stick,
+}
+
+impl MoveInput {
+    /// The movement asked for, as a vector at most 1 long: the stick if it is pushed,
+    /// otherwise the four actions at full speed.
+    pub fn movement(self) -> Vec2 {
+        if self.stick.is_zero() {
+            self.direction().normalized()
+        } else {
+            self.stick.vector()
+        }
+    }
+
+    /// Turns the pressed actions into an un-normalized direction vector.
+    ///
+    /// Opposite actions cancel each other out, so pressing left and right at
+    /// the same time results in no horizontal movement.
+    pub fn direction(self) -> Vec2 {
+        let mut direction = Vec2::ZERO;
+        if self.left {
+            direction.x -= 1.0;
+        }
+        if self.right {
+            direction.x += 1.0;
+        }
+        if self.up {
+            direction.y -= 1.0;
+        }
+        if self.down {
+            direction.y += 1.0;
+        }
+        direction
+    }
+}
+
+/// The direction the player sprite is facing.
+///
+/// The variants are named after the animations in `player.tscn`, which is
+/// why moving towards +Y (down the screen) faces `Up`: the sprite sheet rows
+/// are labelled that way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Facing {
+    Up,
+    #[default]
+    Down,
+    Left,
+    Right,
+}
+
+impl Facing {
+    /// Picks the facing for a non-zero velocity, favouring vertical movement on diagonals.
+    pub fn from_velocity(velocity: Vec2) -> Self {
+        match (velocity.x, velocity.y) {
+            (x, y) if x.abs() > y.abs() && x > 0.0 => Facing::Right,
+            (x, y) if x.abs() > y.abs() && x < 0.0 => Facing::Left,
+            (x, y) if y.abs() >= x.abs() && y > 0.0 => Facing::Up,
+            (x, y) if y.abs() >= x.abs() && y < 0.0 => Facing::Down,
+            _ => Facing::Down,
+        }
+    }
+
+    pub fn run_animation(self) -> &'static str {
+        match self {
+            Facing::Up => "run_up",
+            Facing::Down => "run_down",
+            Facing::Left => "run_left",
+            Facing::Right => "run_right",
+        }
+    }
+
+    /// A unit vector in the direction the player faces; the inverse of `from_velocity`.
+    pub fn vector(self) -> Vec2 {
+        match self {
+            Facing::Up => Vec2::new(0.0, 1.0),
+            Facing::Down => Vec2::new(0.0, -1.0),
+            Facing::Left => Vec2::new(-1.0, 0.0),
+            Facing::Right => Vec2::new(1.0, 0.0),
+        }
+    }
+
+    pub fn idle_animation(self) -> &'static str {
+        match self {
+            Facing::Up => "idle_up",
+            Facing::Down => "idle_down",
+            Facing::Left => "idle_left",
+            Facing::Right => "idle_right",
+        }
+    }
+}
+
+/// The player's position and movement rules, independent of any engine node.
+#[derive(Clone, Debug)]
+pub struct PlayerBody {
+    /// Current position in arena coordinates.
+    pub position: Vec2,
+    /// Movement speed in units per second.
+    pub speed: f32,
+    /// The position is clamped to `0..=bounds` on both axes.
+    pub bounds: Vec2,
+    /// Velocity applied during the last step.
+    pub velocity: Vec2,
+}
+
+impl PlayerBody {
+    pub fn new(speed: f32, bounds: Vec2) -> Self {
+        Self {
+            position: Vec2::ZERO,
+            speed,
+            bounds,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    /// Advances the player by `delta` seconds using the given input.
+    ///
+    /// Returns the new position, which is always inside the bounds.
+    pub fn step(&mut self, input: MoveInput, delta: f32) -> Vec2 {
+        // The movement is at most 1 long, so diagonal movement is not faster than straight movement.
+        self.move_with(input.movement() * self.speed, delta)
+    }
+
+    /// Moves the player with a given velocity for `delta` seconds, e.g. during a dash.
+    ///
+    /// Returns the new position, which is always inside the bounds.
+    pub fn move_with(&mut self, velocity: Vec2, delta: f32) -> Vec2 {
+        self.velocity = velocity;
+        self.position = self.clamp(self.position + self.velocity * delta);
+        self.position
+    }
+
+    /// Clamps a position to the player's bounds.
+    pub fn clamp(&self, position: Vec2) -> Vec2 {
+        Vec2::new(
+            position.x.clamp(0.0, self.bounds.x),
+            position.y.clamp(0.0, self.bounds.y),
+        )
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.velocity.length_squared() > 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body() -> PlayerBody {
+        PlayerBody::new(400.0, Vec2::new(480.0, 720.0))
+    }
+
+    #[test]
+    fn clamp_keeps_positions_in_the_arena() {
+        let body = body();
+        assert_eq!(body.clamp(Vec2::new(-10.0, 800.0)), Vec2::new(0.0, 720.0));
+        assert_eq!(body.clamp(Vec2::new(500.0, -1.0)), Vec2::new(480.0, 0.0));
+        assert_eq!(body.clamp(Vec2::new(240.0, 360.0)), Vec2::new(240.0, 360.0));
+    }
+
+    #[test]
+    fn steps_stop_at_the_arena_edge() {
+        let mut body = body();
+        body.position = Vec2::new(10.0, 10.0);
+        let input = MoveInput {
+            left: true,
+            up: true,
+            ..MoveInput::default()
+        };
+
+        for _ in 0..60 {
+            body.step(input, 1.0 / 60.0);
+        }
+        assert_eq!(body.position, Vec2::ZERO);
+    }
+
+    #[test]
+    fn diagonal_movement_is_not_faster() {
+        let mut body = body();
+        body.position = Vec2::new(240.0, 360.0);
+        body.step(
+            MoveInput {
+                right: true,
+                down: true,
+                ..MoveInput::default()
+            },
+            0.1,
+        );
+        assert!((body.velocity.length() - 400.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stick_moves_in_proportion() {
+        let mut body = body();
+        body.position = Vec2::new(240.0, 360.0);
+        let stick = Stick::from_vector(Vec2::new(0.5, 0.0));
+        body.step(
+            MoveInput {
+                stick,
+                ..MoveInput::default()
+            },
+            1.0,
+        );
+        assert!((body.position.x - 440.0).abs() < 2.0);
+    }
+}
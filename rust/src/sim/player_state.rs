@@ -0,0 +1,253 @@
+use super::math::Vec2;
+use super::player::Facing;
+
+/// Seconds the player staggers after losing a life, before they can move again.
+pub const HURT_TIME: f32 = 0.25;
+
+/// What the player is doing, which decides the animation and whether input is read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerState {
+    /// Standing still, facing the given way.
+    Idle(Facing),
+    /// Moving in the given direction.
+    Run(Facing),
+    /// Dashing; movement input is ignored until the dash ends.
+    Dash,
+    /// Staggering after losing a life; movement input is ignored.
+    Hurt,
+    /// Out of lives.
+    Dead,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        PlayerState::Idle(Facing::default())
+    }
+}
+
+/// Something that happened to the player, fed to `PlayerStateMachine::handle`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerEvent {
+    /// The player moved with this velocity during the last step (zero when standing still).
+    Moved(Vec2),
+    DashStarted,
+    DashEnded,
+    /// A life was lost but the run goes on.
+    Hurt,
+    /// The stagger after losing a life is over.
+    Recovered,
+    /// The last life was lost.
+    Died,
+    /// A new run started.
+    Revived,
+}
+
+/// The player's state, the direction they face and how long they have been in the state.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayerStateMachine {
+    state: PlayerState,
+    facing: Facing,
+    // Seconds since the last state change.
+    elapsed: f32,
+}
+
+impl PlayerStateMachine {
+    pub fn state(&self) -> PlayerState {
+        self.state
+    }
+
+    pub fn facing(&self) -> Facing {
+        self.facing
+    }
+
+    /// Seconds spent in the current state.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Whether movement input should be read in this state.
+    pub fn accepts_input(&self) -> bool {
+        matches!(self.state, PlayerState::Idle(_) | PlayerState::Run(_))
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.state == PlayerState::Dead
+    }
+
+    /// Applies an event and returns the new state.
+    ///
+    /// Events that make no sense in the current state (moving while dead,
+    /// recovering while not hurt, ...) are ignored.
+    pub fn handle(&mut self, event: PlayerEvent) -> PlayerState {
+        let next = match (self.state, event) {
+            (_, PlayerEvent::Revived) => {
+                self.facing = Facing::default();
+                PlayerState::Idle(self.facing)
+            }
+            (PlayerState::Dead, _) => PlayerState::Dead,
+            (_, PlayerEvent::Died) => PlayerState::Dead,
+
+            (PlayerState::Idle(_) | PlayerState::Run(_), PlayerEvent::Moved(velocity)) => {
+                if velocity.length_squared() > 0.0 {
+                    self.facing = Facing::from_velocity(velocity);
+                    PlayerState::Run(self.facing)
+                } else {
+                    PlayerState::Idle(self.facing)
+                }
+            }
+            // A dash keeps its heading, but the player turns to face it.
+            (PlayerState::Dash, PlayerEvent::Moved(velocity)) => {
+                if velocity.length_squared() > 0.0 {
+                    self.facing = Facing::from_velocity(velocity);
+                }
+                PlayerState::Dash
+            }
+
+            (PlayerState::Idle(_) | PlayerState::Run(_), PlayerEvent::DashStarted) => PlayerState::Dash,
+            (PlayerState::Dash, PlayerEvent::DashEnded) => PlayerState::Idle(self.facing),
+
+            (PlayerState::Idle(_) | PlayerState::Run(_) | PlayerState::Dash, PlayerEvent::Hurt) => PlayerState::Hurt,
+            (PlayerState::Hurt, PlayerEvent::Recovered) => PlayerState::Idle(self.facing),
+
+            (state, _) => state,
+        };
+
+        if next != self.state {
+            self.elapsed = 0.0;
+        }
+        self.state = next;
+        next
+    }
+
+    /// Advances the time in the current state by `delta` seconds,
+    /// ending the stagger after `HURT_TIME`. Returns the new state.
+    pub fn step(&mut self, delta: f32) -> PlayerState {
+        self.elapsed += delta;
+        if self.state == PlayerState::Hurt && self.elapsed >= HURT_TIME {
+            return self.handle(PlayerEvent::Recovered);
+        }
+        self.state
+    }
+
+    /// The animation of the player's `AnimatedSprite2D` for the current state.
+    pub fn animation(&self) -> &'static str {
+        match self.state {
+            PlayerState::Idle(facing) => facing.idle_animation(),
+            PlayerState::Run(facing) => facing.run_animation(),
+            PlayerState::Dash => self.facing.run_animation(),
+            PlayerState::Hurt => self.facing.idle_animation(),
+            PlayerState::Dead => "dead",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moving(x: f32, y: f32) -> PlayerEvent {
+        PlayerEvent::Moved(Vec2::new(x, y))
+    }
+
+    #[test]
+    fn starts_idle_facing_down() {
+        let machine = PlayerStateMachine::default();
+        assert_eq!(machine.state(), PlayerState::Idle(Facing::Down));
+        assert_eq!(machine.animation(), "idle_down");
+        assert!(machine.accepts_input());
+    }
+
+    #[test]
+    fn moving_runs_and_stopping_idles_in_the_last_direction() {
+        let mut machine = PlayerStateMachine::default();
+
+        assert_eq!(machine.handle(moving(-400.0, 0.0)), PlayerState::Run(Facing::Left));
+        assert_eq!(machine.animation(), "run_left");
+
+        assert_eq!(machine.handle(moving(0.0, 0.0)), PlayerState::Idle(Facing::Left));
+        assert_eq!(machine.animation(), "idle_left");
+    }
+
+    #[test]
+    fn facing_keeps_the_sprite_sheet_naming() {
+        // Moving down the screen (+Y) plays the "up" animations, see `Facing`.
+        let mut machine = PlayerStateMachine::default();
+        assert_eq!(machine.handle(moving(0.0, 400.0)), PlayerState::Run(Facing::Up));
+        assert_eq!(machine.handle(moving(300.0, 300.0)), PlayerState::Run(Facing::Up));
+        assert_eq!(machine.handle(moving(400.0, -100.0)), PlayerState::Run(Facing::Right));
+    }
+
+    #[test]
+    fn dash_ignores_input_and_ends_idle() {
+        let mut machine = PlayerStateMachine::default();
+        machine.handle(moving(400.0, 0.0));
+
+        assert_eq!(machine.handle(PlayerEvent::DashStarted), PlayerState::Dash);
+        assert!(!machine.accepts_input());
+        assert_eq!(machine.animation(), "run_right");
+
+        assert_eq!(machine.handle(moving(0.0, 0.0)), PlayerState::Dash);
+        assert_eq!(machine.handle(PlayerEvent::DashEnded), PlayerState::Idle(Facing::Right));
+    }
+
+    #[test]
+    fn dash_only_starts_from_idle_or_run() {
+        let mut machine = PlayerStateMachine::default();
+        machine.handle(PlayerEvent::Hurt);
+        assert_eq!(machine.handle(PlayerEvent::DashStarted), PlayerState::Hurt);
+
+        machine.handle(PlayerEvent::Died);
+        assert_eq!(machine.handle(PlayerEvent::DashStarted), PlayerState::Dead);
+    }
+
+    #[test]
+    fn hurt_recovers_after_the_stagger() {
+        let mut machine = PlayerStateMachine::default();
+        machine.handle(moving(0.0, -400.0));
+
+        assert_eq!(machine.handle(PlayerEvent::Hurt), PlayerState::Hurt);
+        assert!(!machine.accepts_input());
+        assert_eq!(machine.animation(), "idle_down");
+
+        assert_eq!(machine.step(HURT_TIME / 2.0), PlayerState::Hurt);
+        assert_eq!(machine.step(HURT_TIME / 2.0), PlayerState::Idle(Facing::Down));
+        assert!(machine.accepts_input());
+    }
+
+    #[test]
+    fn recovered_is_ignored_unless_hurt() {
+        let mut machine = PlayerStateMachine::default();
+        machine.handle(moving(400.0, 0.0));
+        assert_eq!(machine.handle(PlayerEvent::Recovered), PlayerState::Run(Facing::Right));
+        assert_eq!(machine.handle(PlayerEvent::DashEnded), PlayerState::Run(Facing::Right));
+    }
+
+    #[test]
+    fn dead_is_final_until_revived() {
+        let mut machine = PlayerStateMachine::default();
+        machine.handle(moving(-400.0, 0.0));
+
+        assert_eq!(machine.handle(PlayerEvent::Died), PlayerState::Dead);
+        assert_eq!(machine.animation(), "dead");
+        assert!(machine.is_dead());
+
+        for event in [moving(400.0, 0.0), PlayerEvent::DashStarted, PlayerEvent::Hurt, PlayerEvent::Recovered] {
+            assert_eq!(machine.handle(event), PlayerState::Dead);
+        }
+        assert_eq!(machine.step(10.0), PlayerState::Dead);
+
+        assert_eq!(machine.handle(PlayerEvent::Revived), PlayerState::Idle(Facing::Down));
+        assert!(machine.accepts_input());
+    }
+
+    #[test]
+    fn elapsed_resets_on_state_changes_only() {
+        let mut machine = PlayerStateMachine::default();
+        machine.step(1.0);
+        machine.handle(moving(0.0, 0.0));
+        assert_eq!(machine.elapsed(), 1.0);
+
+        machine.handle(moving(400.0, 0.0));
+        assert_eq!(machine.elapsed(), 0.0);
+    }
+}
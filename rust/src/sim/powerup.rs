@@ -0,0 +1,310 @@
+use super::math::Vec2;
+
+use rand::Rng;
+use std::fmt;
+
+/// How much slower the world runs while slow motion is active.
+pub const SLOW_MOTION_SCALE: f32 = 0.5;
+
+/// Size of the player while shrunk.
+pub const SHRINK_SCALE: f32 = 0.5;
+
+/// Points per score tick while the score multiplier is active.
+pub const SCORE_MULTIPLIER: i32 = 2;
+
+/// A collectible that changes the rules for a while.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+    /// Absorbs the next hit.
+    Shield,
+    /// Slows everything but the player down.
+    SlowMotion,
+    /// Makes the player smaller and easier to fit between mobs.
+    Shrink,
+    /// Multiplies the points of every score tick.
+    ScoreMultiplier,
+    /// Clears every mob off the screen at once.
+    Bomb,
+}
+
+impl PowerUpKind {
+    /// Every kind, in the order of `index`.
+    pub const ALL: [PowerUpKind; 5] = [
+        PowerUpKind::Shield,
+        PowerUpKind::SlowMotion,
+        PowerUpKind::Shrink,
+        PowerUpKind::ScoreMultiplier,
+        PowerUpKind::Bomb,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: i64) -> Option<Self> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
+
+    /// The name of the kind in config files, e.g. "slow_motion".
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUpKind::Shield => "shield",
+            PowerUpKind::SlowMotion => "slow_motion",
+            PowerUpKind::Shrink => "shrink",
+            PowerUpKind::ScoreMultiplier => "score_multiplier",
+            PowerUpKind::Bomb => "bomb",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Seconds the power-up lasts once collected; 0 for the ones that act instantly.
+    pub fn duration(self) -> f32 {
+        match self {
+            PowerUpKind::Shield => 8.0,
+            PowerUpKind::SlowMotion => 4.0,
+            PowerUpKind::Shrink => 6.0,
+            PowerUpKind::ScoreMultiplier => 10.0,
+            PowerUpKind::Bomb => 0.0,
+        }
+    }
+
+    /// Short text shown on the pickup and its HUD icon.
+    pub fn icon(self) -> &'static str {
+        match self {
+            PowerUpKind::Shield => "S",
+            PowerUpKind::SlowMotion => "T",
+            PowerUpKind::Shrink => "-",
+            PowerUpKind::ScoreMultiplier => "x2",
+            PowerUpKind::Bomb => "B",
+        }
+    }
+}
+
+impl fmt::Display for PowerUpKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::SlowMotion => "Slow motion",
+            PowerUpKind::Shrink => "Shrink",
+            PowerUpKind::ScoreMultiplier => "Score x2",
+            PowerUpKind::Bomb => "Bomb",
+        })
+    }
+}
+
+/// A collected power-up that is still running.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActivePowerUp {
+    pub kind: PowerUpKind,
+    /// Seconds left.
+    pub remaining: f32,
+}
+
+impl ActivePowerUp {
+    /// How much of the duration is left, from 1 (just collected) to 0.
+    pub fn fraction(&self) -> f32 {
+        let duration = self.kind.duration();
+        if duration > 0.0 {
+            (self.remaining / duration).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The power-ups currently affecting the player.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PowerUps {
+    active: Vec<ActivePowerUp>,
+}
+
+impl PowerUps {
+    /// Starts a power-up, or restarts its countdown if it is already running.
+    ///
+    /// Returns `false` for power-ups without a duration, which the caller
+    /// has to apply on the spot.
+    pub fn activate(&mut self, kind: PowerUpKind) -> bool {
+        let remaining = kind.duration();
+        if remaining <= 0.0 {
+            return false;
+        }
+
+        match self.active.iter_mut().find(|active| active.kind == kind) {
+            Some(active) => active.remaining = remaining,
+            None => self.active.push(ActivePowerUp { kind, remaining }),
+        }
+        true
+    }
+
+    /// Ends a power-up early, e.g. a shield that absorbed a hit.
+    /// Returns whether it was running.
+    pub fn deactivate(&mut self, kind: PowerUpKind) -> bool {
+        let count = self.active.len();
+        self.active.retain(|active| active.kind != kind);
+        self.active.len() != count
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn get(&self, kind: PowerUpKind) -> Option<&ActivePowerUp> {
+        self.active.iter().find(|active| active.kind == kind)
+    }
+
+    /// The running power-ups, in the order they were collected.
+    pub fn active(&self) -> &[ActivePowerUp] {
+        &self.active
+    }
+
+    /// Counts the power-ups down by `delta` seconds and returns the ones that ran out.
+    pub fn step(&mut self, delta: f32) -> Vec<PowerUpKind> {
+        let mut expired = Vec::new();
+        self.active.retain_mut(|active| {
+            active.remaining -= delta;
+            if active.remaining <= 0.0 {
+                expired.push(active.kind);
+                false
+            } else {
+                true
+            }
+        });
+        expired
+    }
+
+    /// Points added per score tick.
+    pub fn score_multiplier(&self) -> i32 {
+        if self.is_active(PowerUpKind::ScoreMultiplier) {
+            SCORE_MULTIPLIER
+        } else {
+            1
+        }
+    }
+}
+
+/// A pickup waiting to be collected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickupSpawn {
+    pub kind: PowerUpKind,
+    pub position: Vec2,
+}
+
+/// Picks a random power-up and a spot for it at least `margin` away from the arena edges.
+pub fn spawn_pickup<R: Rng + ?Sized>(arena: Vec2, margin: f32, rng: &mut R) -> PickupSpawn {
+    let kind = PowerUpKind::ALL[rng.random_range(0..PowerUpKind::ALL.len())];
+
+    // Fall back to the centre if the arena is too small for the margin.
+    let axis = |size: f32, rng: &mut R| {
+        if size > margin * 2.0 {
+            rng.random_range(margin..size - margin)
+        } else {
+            size / 2.0
+        }
+    };
+    let x = axis(arena.x, rng);
+    let y = axis(arena.y, rng);
+
+    PickupSpawn {
+        kind,
+        position: Vec2::new(x, y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::rng::GameRng;
+
+    #[test]
+    fn activating_a_running_power_up_restarts_it() {
+        let mut power_ups = PowerUps::default();
+        assert!(power_ups.activate(PowerUpKind::Shield));
+        power_ups.step(5.0);
+        assert!(power_ups.activate(PowerUpKind::Shield));
+
+        assert_eq!(
+            power_ups.active(),
+            [ActivePowerUp {
+                kind: PowerUpKind::Shield,
+                remaining: PowerUpKind::Shield.duration(),
+            }]
+        );
+        assert_eq!(power_ups.get(PowerUpKind::Shield).unwrap().fraction(), 1.0);
+    }
+
+    #[test]
+    fn bombs_act_on_the_spot() {
+        let mut power_ups = PowerUps::default();
+        assert!(!power_ups.activate(PowerUpKind::Bomb));
+        assert!(power_ups.active().is_empty());
+    }
+
+    #[test]
+    fn power_ups_expire_in_order_of_their_time_left() {
+        let mut power_ups = PowerUps::default();
+        for kind in [PowerUpKind::ScoreMultiplier, PowerUpKind::SlowMotion, PowerUpKind::Shrink] {
+            power_ups.activate(kind);
+        }
+        assert_eq!(power_ups.score_multiplier(), SCORE_MULTIPLIER);
+
+        assert_eq!(power_ups.step(3.0), []);
+        assert_eq!(power_ups.step(1.0), [PowerUpKind::SlowMotion]);
+        assert_eq!(power_ups.step(2.0), [PowerUpKind::Shrink]);
+        assert_eq!(power_ups.active().len(), 1);
+        assert_eq!(power_ups.step(10.0), [PowerUpKind::ScoreMultiplier]);
+        assert_eq!(power_ups.score_multiplier(), 1);
+    }
+
+    #[test]
+    fn power_ups_running_out_together_keep_their_collection_order() {
+        let mut power_ups = PowerUps::default();
+        for kind in [PowerUpKind::Shrink, PowerUpKind::SlowMotion, PowerUpKind::Shield] {
+            power_ups.activate(kind);
+        }
+        assert_eq!(
+            power_ups.step(20.0),
+            [PowerUpKind::Shrink, PowerUpKind::SlowMotion, PowerUpKind::Shield]
+        );
+    }
+
+    #[test]
+    fn deactivating_reports_whether_it_was_running() {
+        let mut power_ups = PowerUps::default();
+        power_ups.activate(PowerUpKind::Shield);
+        assert!(power_ups.deactivate(PowerUpKind::Shield));
+        assert!(!power_ups.deactivate(PowerUpKind::Shield));
+        assert!(!power_ups.is_active(PowerUpKind::Shield));
+    }
+
+    #[test]
+    fn pickups_keep_the_margin_from_the_edges() {
+        let mut rng = GameRng::new(11);
+        for _ in 0..200 {
+            let pickup = spawn_pickup(Vec2::new(480.0, 720.0), 40.0, &mut rng);
+            assert!((40.0..=440.0).contains(&pickup.position.x), "{pickup:?}");
+            assert!((40.0..=680.0).contains(&pickup.position.y), "{pickup:?}");
+        }
+    }
+
+    #[test]
+    fn pickups_fall_back_to_the_centre_of_a_small_arena() {
+        let mut rng = GameRng::new(11);
+        // Too narrow for the margin, but tall enough.
+        let pickup = spawn_pickup(Vec2::new(60.0, 720.0), 40.0, &mut rng);
+        assert_eq!(pickup.position.x, 30.0);
+        assert!((40.0..=680.0).contains(&pickup.position.y));
+
+        // Exactly twice the margin is too small as well.
+        let pickup = spawn_pickup(Vec2::new(80.0, 80.0), 40.0, &mut rng);
+        assert_eq!(pickup.position, Vec2::new(40.0, 40.0));
+    }
+}
@@ -0,0 +1,255 @@
+use super::bytes::{ByteReader, Truncated};
+use super::difficulty::DifficultyPreset;
+use super::player::{MoveInput, Stick};
+
+use std::fmt;
+
+/// Identifies a replay file.
+const MAGIC: &[u8; 4] = b"DGRP";
+
+/// Version of the binary layout written by `Replay::encode`.
+///
+/// - 1: seed and frames.
+/// - 2: adds the difficulty preset after the seed.
+/// - 3: adds the analogue stick to every run of frames.
+const VERSION: u8 = 3;
+
+/// A recorded run: the seed and difficulty it was played with and the movement
+/// input of every physics frame.
+///
+/// Feeding the same frames into a run started with the same seed and difficulty reproduces it exactly.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: DifficultyPreset,
+    pub frames: Vec<MoveInput>,
+}
+
+/// Why a replay file could not be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The data does not start with the replay magic bytes.
+    NotAReplay,
+    /// The file was written by a newer version of the game.
+    UnsupportedVersion(u8),
+    /// The data ends in the middle of a field.
+    Truncated,
+    /// The runs add up to a different number of frames than the header says.
+    FrameCountMismatch,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(version) => write!(f, "unsupported replay version {version}"),
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+            ReplayError::FrameCountMismatch => write!(f, "replay frame count does not match its data"),
+        }
+    }
+}
+
+impl From<Truncated> for ReplayError {
+    fn from(_: Truncated) -> Self {
+        ReplayError::Truncated
+    }
+}
+
+impl MoveInput {
+    /// Packs the four movement actions into the low four bits of a byte and the dash into the fifth.
+    /// The stick is stored separately.
+    ///
+    /// Replays recorded before the dash existed never set the fifth bit, so they still read the same.
+    pub fn to_bits(self) -> u8 {
+        (self.left as u8)
+            | (self.right as u8) << 1
+            | (self.up as u8) << 2
+            | (self.down as u8) << 3
+            | (self.dash as u8) << 4
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            left: bits & 1 != 0,
+            right: bits & 1 << 1 != 0,
+            up: bits & 1 << 2 != 0,
+            down: bits & 1 << 3 != 0,
+            dash: bits & 1 << 4 != 0,
+            stick: Stick::default(),
+        }
+    }
+}
+
+impl Replay {
+    pub fn new(seed: u64, difficulty: DifficultyPreset) -> Self {
+        Self {
+            seed,
+            difficulty,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Serializes the replay into its compact binary form.
+    ///
+    /// Layout (little endian): magic, version byte, seed (u64), difficulty preset index (u8), frame count (u32),
+    /// then run-length encoded frames as (input bits: u8, stick x: i8, stick y: i8, run length: u16) tuples.
+    /// Input rarely changes from one frame to the next, so runs keep files small.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(18 + self.frames.len() / 8);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.difficulty.index() as u8);
+        bytes.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+
+        let mut frames = self.frames.iter().peekable();
+        while let Some(frame) = frames.next() {
+            let mut run: u16 = 1;
+            while run < u16::MAX && frames.peek() == Some(&frame) {
+                frames.next();
+                run += 1;
+            }
+            bytes.push(frame.to_bits());
+            bytes.extend_from_slice(&frame.stick.x.to_le_bytes());
+            bytes.extend_from_slice(&frame.stick.y.to_le_bytes());
+            bytes.extend_from_slice(&run.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Reads a replay written by `encode`, in this or any earlier version.
+    pub fn decode(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = ByteReader::new(bytes);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+
+        let version = reader.u8()?;
+        if version == 0 || version > VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = reader.u64()?;
+        // Version 1 replays were all played on the default difficulty.
+        let difficulty = if version >= 2 {
+            DifficultyPreset::from_index(i64::from(reader.u8()?))
+        } else {
+            DifficultyPreset::Normal
+        };
+        let frame_count = reader.u32()? as usize;
+
+        // The frame count in the header is not trusted to size anything: every run is
+        // checked against it before it is expanded, so a corrupt file cannot ask for more.
+        let mut frames = Vec::new();
+        while !reader.is_empty() {
+            let mut frame = MoveInput::from_bits(reader.u8()?);
+            // Replays before version 3 only have the four actions.
+            if version >= 3 {
+                frame.stick = Stick {
+                    x: reader.u8()? as i8,
+                    y: reader.u8()? as i8,
+                };
+            }
+            let run = reader.u16()?;
+            if frames.len() + run as usize > frame_count {
+                return Err(ReplayError::FrameCountMismatch);
+            }
+            frames.extend(std::iter::repeat_n(frame, run as usize));
+        }
+
+        if frames.len() != frame_count {
+            return Err(ReplayError::FrameCountMismatch);
+        }
+
+        Ok(Self {
+            seed,
+            difficulty,
+            frames,
+        })
+    }
+}
+
+/// Hands out the frames of a replay one physics frame at a time.
+#[derive(Clone, Debug)]
+pub struct ReplayCursor {
+    frames: Vec<MoveInput>,
+    position: usize,
+}
+
+impl ReplayCursor {
+    pub fn new(frames: Vec<MoveInput>) -> Self {
+        Self { frames, position: 0 }
+    }
+
+    /// The input for the next frame, or `None` once the recording has run out.
+    pub fn next_frame(&mut self) -> Option<MoveInput> {
+        let frame = self.frames.get(self.position).copied();
+        if frame.is_some() {
+            self.position += 1;
+        }
+        frame
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.frames.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(frame_count: u32) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&7u64.to_le_bytes());
+        bytes.push(DifficultyPreset::Normal.index() as u8);
+        bytes.extend_from_slice(&frame_count.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn encoding_round_trips() {
+        let mut replay = Replay::new(1234, DifficultyPreset::Hard);
+        let left = MoveInput {
+            left: true,
+            ..MoveInput::default()
+        };
+        let stick = MoveInput {
+            stick: Stick { x: -64, y: 127 },
+            ..MoveInput::default()
+        };
+        replay.frames.extend(std::iter::repeat_n(left, 70_000));
+        replay.frames.extend([stick, MoveInput::default(), stick]);
+
+        assert_eq!(Replay::decode(&replay.encode()), Ok(replay));
+    }
+
+    #[test]
+    fn runs_past_the_frame_count_are_rejected() {
+        // A single run of the longest length claims far more frames than the header.
+        let mut bytes = header(10);
+        bytes.extend_from_slice(&[0, 0, 0]);
+        bytes.extend_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(Replay::decode(&bytes), Err(ReplayError::FrameCountMismatch));
+    }
+
+    #[test]
+    fn a_huge_frame_count_is_not_trusted() {
+        // The header claims the most frames possible, but the runs only add up to one.
+        let mut bytes = header(u32::MAX);
+        bytes.extend_from_slice(&[0, 0, 0]);
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        assert_eq!(Replay::decode(&bytes), Err(ReplayError::FrameCountMismatch));
+    }
+
+    #[test]
+    fn truncated_and_foreign_files_are_rejected() {
+        let mut bytes = header(1);
+        bytes.extend_from_slice(&[0, 0, 0, 1]);
+        assert_eq!(Replay::decode(&bytes), Err(ReplayError::Truncated));
+        assert_eq!(Replay::decode(b"RIFF...."), Err(ReplayError::NotAReplay));
+    }
+}
@@ -0,0 +1,106 @@
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The single source of randomness for a run.
+///
+/// Every random decision (spawn position, heading, speed, sound choice) goes
+/// through this generator, so two runs started with the same seed and the
+/// same input play out identically.
+///
+/// It is backed by ChaCha8 rather than `StdRng`, whose algorithm may change
+/// between versions of `rand`: seeds and replays must keep reproducing their runs.
+#[derive(Clone, Debug)]
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Picks a fresh, non-zero seed from the operating system's entropy.
+    ///
+    /// Seeds are kept to 32 bits so they are short enough to read off the
+    /// game-over screen and type back in.
+    pub fn random_seed() -> u64 {
+        rand::rng().random_range(1..=u64::from(u32::MAX))
+    }
+
+    /// The seed this generator was created or last reseeded with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A generator for the same seed on its own stream, numbered `stream`.
+    ///
+    /// It follows the seed like this one does, but drawing from it leaves this
+    /// one's sequence alone, e.g. for sound variations that must not change the run.
+    pub fn substream(&self, stream: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(stream);
+        Self {
+            seed: self.seed,
+            rng,
+        }
+    }
+
+    /// Restarts the sequence from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.rng.fill_bytes(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_sequence_of_a_seed_never_changes() {
+        // Saved replays depend on these exact numbers. If this fails, the generator changed
+        // and every replay recorded so far plays out differently.
+        let mut rng = GameRng::new(1);
+        let first: Vec<u32> = (0..3).map(|_| rng.next_u32()).collect();
+        assert_eq!(first, [2359561649, 1728662762, 4228812395]);
+    }
+
+    #[test]
+    fn substreams_follow_the_seed_without_touching_the_run() {
+        let mut rng = GameRng::new(5);
+        let mut sounds = rng.substream(1);
+        let sound = sounds.next_u64();
+        assert_eq!(rng.substream(1).next_u64(), sound);
+
+        // Drawing from the substream does not move the run's own sequence.
+        assert_eq!(rng.next_u64(), GameRng::new(5).next_u64());
+        assert_ne!(sound, GameRng::new(5).next_u64());
+    }
+
+    #[test]
+    fn reseeding_restarts_the_sequence() {
+        let mut rng = GameRng::new(99);
+        let first = rng.next_u64();
+        rng.next_u64();
+        rng.reseed(99);
+        assert_eq!(rng.next_u64(), first);
+        assert_eq!(rng.seed(), 99);
+    }
+}
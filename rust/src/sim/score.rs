@@ -0,0 +1,136 @@
+/// A repeating countdown, the simulation's stand-in for a Godot `Timer`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Countdown {
+    wait_time: f32,
+    time_left: f32,
+    one_shot: bool,
+    running: bool,
+}
+
+impl Countdown {
+    pub fn new(wait_time: f32, one_shot: bool) -> Self {
+        Self {
+            wait_time,
+            time_left: wait_time,
+            one_shot,
+            running: false,
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.time_left = self.wait_time;
+        self.running = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn wait_time(&self) -> f32 {
+        self.wait_time
+    }
+
+    /// Changes the interval used from the next restart on, like `Timer::set_wait_time`.
+    pub fn set_wait_time(&mut self, wait_time: f32) {
+        self.wait_time = wait_time;
+    }
+
+    /// Advances the countdown and returns how many times it fired.
+    pub fn step(&mut self, delta: f32) -> u32 {
+        if !self.running || self.wait_time <= 0.0 {
+            return 0;
+        }
+
+        let mut fired = 0;
+        self.time_left -= delta;
+
+        while self.running && self.time_left <= 0.0 {
+            fired += 1;
+            if self.one_shot {
+                self.running = false;
+            } else {
+                self.time_left += self.wait_time;
+            }
+        }
+
+        fired
+    }
+}
+
+/// The run's score, which goes up by one point every score tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    value: i32,
+}
+
+impl Score {
+    pub fn reset(&mut self) {
+        self.value = 0;
+    }
+
+    /// Adds one tick worth of score and returns the new value.
+    pub fn tick(&mut self) -> i32 {
+        self.value += 1;
+        self.value
+    }
+
+    /// Adds `points` at once and returns the new value.
+    pub fn add(&mut self, points: i32) -> i32 {
+        self.value += points;
+        self.value
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn countdown_does_nothing_until_started() {
+        let mut countdown = Countdown::new(1.0, false);
+        assert_eq!(countdown.step(5.0), 0);
+        assert!(!countdown.is_running());
+    }
+
+    #[test]
+    fn repeating_countdown_fires_and_rearms() {
+        let mut countdown = Countdown::new(0.5, false);
+        countdown.start();
+
+        assert_eq!(countdown.step(0.4), 0);
+        assert_eq!(countdown.step(0.2), 1);
+        // The leftover time carries over, so long frames fire several times.
+        assert_eq!(countdown.step(1.0), 2);
+        assert!(countdown.is_running());
+    }
+
+    #[test]
+    fn one_shot_countdown_fires_once() {
+        let mut countdown = Countdown::new(2.0, true);
+        countdown.start();
+
+        assert_eq!(countdown.step(10.0), 1);
+        assert!(!countdown.is_running());
+        assert_eq!(countdown.step(10.0), 0);
+
+        countdown.start();
+        assert_eq!(countdown.step(2.0), 1);
+    }
+
+    #[test]
+    fn new_wait_time_applies_on_restart() {
+        let mut countdown = Countdown::new(1.0, false);
+        countdown.set_wait_time(3.0);
+        countdown.start();
+        assert_eq!(countdown.step(2.5), 0);
+        assert_eq!(countdown.step(0.5), 1);
+    }
+}
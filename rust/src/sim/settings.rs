@@ -0,0 +1,214 @@
+use super::config::{self, ConfigError, Section, Value};
+
+use std::fmt;
+
+/// The player's options, saved between sessions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// Linear volumes from 0 (muted) to 1.
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    /// Whether fireball sounds are panned by where the mobs come in.
+    pub positional_audio: bool,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub screen_shake: bool,
+    /// Uses colours that stay apart for colourblind players.
+    pub colorblind_palette: bool,
+    /// Moves the player towards the mouse cursor or touch point instead of with the joystick.
+    pub follow_pointer: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 1.0,
+            positional_audio: true,
+            fullscreen: false,
+            vsync: true,
+            screen_shake: true,
+            colorblind_palette: false,
+            follow_pointer: false,
+        }
+    }
+}
+
+/// A volume slider of the settings menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VolumeSetting {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl VolumeSetting {
+    pub const ALL: [VolumeSetting; 3] = [VolumeSetting::Master, VolumeSetting::Music, VolumeSetting::Sfx];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: i64) -> Option<Self> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
+
+    /// The name of the audio bus the volume applies to.
+    pub fn bus(self) -> &'static str {
+        match self {
+            VolumeSetting::Master => "Master",
+            VolumeSetting::Music => "Music",
+            VolumeSetting::Sfx => "SFX",
+        }
+    }
+
+    pub fn get(self, settings: &Settings) -> f32 {
+        match self {
+            VolumeSetting::Master => settings.master_volume,
+            VolumeSetting::Music => settings.music_volume,
+            VolumeSetting::Sfx => settings.sfx_volume,
+        }
+    }
+
+    pub fn set(self, settings: &mut Settings, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        match self {
+            VolumeSetting::Master => settings.master_volume = volume,
+            VolumeSetting::Music => settings.music_volume = volume,
+            VolumeSetting::Sfx => settings.sfx_volume = volume,
+        }
+    }
+}
+
+impl fmt::Display for VolumeSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VolumeSetting::Master => "Master volume",
+            VolumeSetting::Music => "Music volume",
+            VolumeSetting::Sfx => "Effects volume",
+        })
+    }
+}
+
+/// An on/off option of the settings menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToggleSetting {
+    PositionalAudio,
+    Fullscreen,
+    Vsync,
+    ScreenShake,
+    ColorblindPalette,
+    FollowPointer,
+}
+
+impl ToggleSetting {
+    pub const ALL: [ToggleSetting; 6] = [
+        ToggleSetting::PositionalAudio,
+        ToggleSetting::Fullscreen,
+        ToggleSetting::Vsync,
+        ToggleSetting::ScreenShake,
+        ToggleSetting::ColorblindPalette,
+        ToggleSetting::FollowPointer,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: i64) -> Option<Self> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
+
+    pub fn get(self, settings: &Settings) -> bool {
+        match self {
+            ToggleSetting::PositionalAudio => settings.positional_audio,
+            ToggleSetting::Fullscreen => settings.fullscreen,
+            ToggleSetting::Vsync => settings.vsync,
+            ToggleSetting::ScreenShake => settings.screen_shake,
+            ToggleSetting::ColorblindPalette => settings.colorblind_palette,
+            ToggleSetting::FollowPointer => settings.follow_pointer,
+        }
+    }
+
+    pub fn set(self, settings: &mut Settings, on: bool) {
+        match self {
+            ToggleSetting::PositionalAudio => settings.positional_audio = on,
+            ToggleSetting::Fullscreen => settings.fullscreen = on,
+            ToggleSetting::Vsync => settings.vsync = on,
+            ToggleSetting::ScreenShake => settings.screen_shake = on,
+            ToggleSetting::ColorblindPalette => settings.colorblind_palette = on,
+            ToggleSetting::FollowPointer => settings.follow_pointer = on,
+        }
+    }
+}
+
+impl fmt::Display for ToggleSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ToggleSetting::PositionalAudio => "Stereo fireballs",
+            ToggleSetting::Fullscreen => "Fullscreen",
+            ToggleSetting::Vsync => "V-Sync",
+            ToggleSetting::ScreenShake => "Screen shake",
+            ToggleSetting::ColorblindPalette => "Colourblind colours",
+            ToggleSetting::FollowPointer => "Follow the pointer",
+        })
+    }
+}
+
+impl Settings {
+    /// Reads settings written by `to_config`; missing sections and keys keep their default.
+    pub fn from_config(text: &str) -> Result<Self, ConfigError> {
+        let sections = config::parse(text)?;
+        let empty = Section::default();
+        let section = |name: &str| sections.iter().find(|section| section.name == name).unwrap_or(&empty);
+        let audio = section("audio");
+        let display = section("display");
+        let controls = section("controls");
+
+        let defaults = Self::default();
+        let volume = |key: &str, default: f32| -> Result<f32, ConfigError> {
+            Ok((audio.number(key, default.into())? as f32).clamp(0.0, 1.0))
+        };
+
+        Ok(Self {
+            master_volume: volume("master_volume", defaults.master_volume)?,
+            music_volume: volume("music_volume", defaults.music_volume)?,
+            sfx_volume: volume("sfx_volume", defaults.sfx_volume)?,
+            positional_audio: audio.boolean("positional", defaults.positional_audio)?,
+            fullscreen: display.boolean("fullscreen", defaults.fullscreen)?,
+            vsync: display.boolean("vsync", defaults.vsync)?,
+            screen_shake: display.boolean("screen_shake", defaults.screen_shake)?,
+            colorblind_palette: display.boolean("colorblind_palette", defaults.colorblind_palette)?,
+            follow_pointer: controls.boolean("follow_pointer", defaults.follow_pointer)?,
+        })
+    }
+
+    /// Writes the settings in the config file format read by `from_config`.
+    pub fn to_config(&self) -> String {
+        // Volumes are kept to whole percent, so the file does not fill up with f32 rounding noise.
+        let volume = |volume: f32| Value::Number((f64::from(volume) * 100.0).round() / 100.0);
+
+        let mut audio = Section::new("audio");
+        audio.set("master_volume", volume(self.master_volume));
+        audio.set("music_volume", volume(self.music_volume));
+        audio.set("sfx_volume", volume(self.sfx_volume));
+        audio.set("positional", Value::Bool(self.positional_audio));
+
+        let mut display = Section::new("display");
+        display.set("fullscreen", Value::Bool(self.fullscreen));
+        display.set("vsync", Value::Bool(self.vsync));
+        display.set("screen_shake", Value::Bool(self.screen_shake));
+        display.set("colorblind_palette", Value::Bool(self.colorblind_palette));
+
+        let mut controls = Section::new("controls");
+        controls.set("follow_pointer", Value::Bool(self.follow_pointer));
+
+        config::write(&[audio, display, controls])
+    }
+}
@@ -0,0 +1,60 @@
+use super::math::Vec2;
+
+/// Offset in pixels of the strongest shake.
+pub const MAX_OFFSET: f32 = 12.0;
+
+/// How much trauma wears off per second.
+pub const RECOVERY_RATE: f32 = 1.5;
+
+/// Trauma added when the player loses a life.
+pub const HIT_TRAUMA: f32 = 0.6;
+
+/// Trauma added when a bomb clears the screen.
+pub const BOMB_TRAUMA: f32 = 0.4;
+
+/// A screen shake that builds up with "trauma" from hits and wears off over time.
+///
+/// The offset grows with the square of the trauma, so small knocks barely move the
+/// screen while big ones shake it hard.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScreenShake {
+    trauma: f32,
+    // Seconds since the shake started, which drives the wobble.
+    time: f32,
+}
+
+impl ScreenShake {
+    /// Adds trauma, up to a full shake.
+    pub fn add_trauma(&mut self, amount: f32) {
+        if self.trauma <= 0.0 {
+            self.time = 0.0;
+        }
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Wears the trauma off by `delta` seconds and returns the offset to apply to the screen.
+    pub fn step(&mut self, delta: f32) -> Vec2 {
+        self.trauma = (self.trauma - RECOVERY_RATE * delta).max(0.0);
+        self.time += delta;
+        self.offset()
+    }
+
+    /// The offset of the screen for the current trauma.
+    pub fn offset(&self) -> Vec2 {
+        let strength = self.trauma * self.trauma * MAX_OFFSET;
+        // Two sines of unrelated frequencies make a wobble that does not look periodic,
+        // without drawing from the run's seeded generator.
+        Vec2::new(
+            (self.time * 47.0).sin() * strength,
+            (self.time * 61.0 + 1.3).sin() * strength,
+        )
+    }
+
+    pub fn is_shaking(&self) -> bool {
+        self.trauma > 0.0
+    }
+
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+}
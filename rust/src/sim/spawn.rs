@@ -0,0 +1,219 @@
+use super::math::Vec2;
+
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// A closed polyline that mobs spawn on, like the `MobPath` curve in the main scene.
+#[derive(Clone, Debug)]
+pub struct PerimeterPath {
+    points: Vec<Vec2>,
+    /// Distance from the first point to the start of each segment.
+    offsets: Vec<f32>,
+    length: f32,
+}
+
+impl PerimeterPath {
+    /// Builds a path through `points`, closing it back to the first point.
+    pub fn new(points: Vec<Vec2>) -> Self {
+        let mut offsets = Vec::with_capacity(points.len());
+        let mut length = 0.0;
+
+        for (i, &start) in points.iter().enumerate() {
+            let end = points[(i + 1) % points.len()];
+            offsets.push(length);
+            length += start.distance_to(end);
+        }
+
+        Self {
+            points,
+            offsets,
+            length,
+        }
+    }
+
+    /// A clockwise rectangle from the origin to `size`, matching the default `MobPath`.
+    pub fn rect(size: Vec2) -> Self {
+        Self::new(vec![
+            Vec2::ZERO,
+            Vec2::new(size.x, 0.0),
+            size,
+            Vec2::new(0.0, size.y),
+        ])
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Returns the position and tangent angle at `offset` units along the path.
+    ///
+    /// Offsets wrap around the path like a looping `PathFollow2D` progress.
+    pub fn sample(&self, offset: f32) -> (Vec2, f32) {
+        if self.points.is_empty() || self.length <= 0.0 {
+            return (self.points.first().copied().unwrap_or_default(), 0.0);
+        }
+
+        let offset = offset.rem_euclid(self.length);
+
+        // Find the last segment that starts before the offset.
+        let index = self
+            .offsets
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1);
+
+        let start = self.points[index];
+        let end = self.points[(index + 1) % self.points.len()];
+        let segment = end - start;
+        let segment_length = segment.length();
+
+        let weight = if segment_length > 0.0 {
+            (offset - self.offsets[index]) / segment_length
+        } else {
+            0.0
+        };
+
+        (start.lerp(end, weight), segment.angle())
+    }
+}
+
+/// Tuning values used when a mob is spawned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpawnParams {
+    pub min_speed: f32,
+    pub max_speed: f32,
+    /// Maximum deviation in radians from pointing straight into the arena.
+    pub heading_spread: f32,
+}
+
+impl Default for SpawnParams {
+    fn default() -> Self {
+        Self {
+            min_speed: 300.0,
+            max_speed: 600.0,
+            heading_spread: PI / 4.0,
+        }
+    }
+}
+
+/// Where and how a new mob enters the arena.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MobSpawn {
+    pub position: Vec2,
+    /// Heading in radians; the mob's rotation is set to it as well.
+    pub direction: f32,
+    pub speed: f32,
+}
+
+impl MobSpawn {
+    pub fn velocity(&self) -> Vec2 {
+        Vec2::new(self.speed, 0.0).rotated(self.direction)
+    }
+}
+
+/// Picks a random point on `path` and a heading pointing into the arena.
+pub fn spawn_mob<R: Rng + ?Sized>(path: &PerimeterPath, params: &SpawnParams, rng: &mut R) -> MobSpawn {
+    // A random point anywhere along the perimeter.
+    let progress = rng.random_range(0.0..path.length().max(f32::EPSILON));
+    let (position, rotation) = path.sample(progress);
+
+    // Perpendicular to the path points inwards for a clockwise path,
+    // then jitter it so mobs do not all fly straight across.
+    let mut direction = rotation + PI / 2.0;
+    if params.heading_spread > 0.0 {
+        direction += rng.random_range(-params.heading_spread..params.heading_spread);
+    }
+
+    let speed = if params.max_speed > params.min_speed {
+        rng.random_range(params.min_speed..params.max_speed)
+    } else {
+        params.min_speed
+    };
+
+    MobSpawn {
+        position,
+        direction,
+        speed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::rng::GameRng;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance_to(b) < 1e-3
+    }
+
+    #[test]
+    fn sample_walks_the_rect_clockwise() {
+        let path = PerimeterPath::rect(Vec2::new(480.0, 720.0));
+        assert_eq!(path.length(), 2400.0);
+
+        let (position, angle) = path.sample(240.0);
+        assert!(close(position, Vec2::new(240.0, 0.0)));
+        assert!(angle.abs() < 1e-6);
+
+        let (position, angle) = path.sample(480.0 + 360.0);
+        assert!(close(position, Vec2::new(480.0, 360.0)));
+        assert!((angle - PI / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sample_wraps_around_the_path() {
+        let path = PerimeterPath::rect(Vec2::new(480.0, 720.0));
+        for offset in [0.0, 100.0, 900.0, 2399.0] {
+            let (position, _) = path.sample(offset);
+            assert!(close(path.sample(offset + path.length()).0, position));
+            assert!(close(path.sample(offset - 2.0 * path.length()).0, position));
+        }
+    }
+
+    #[test]
+    fn sample_of_an_empty_path_is_the_origin() {
+        assert_eq!(PerimeterPath::new(Vec::new()).sample(10.0), (Vec2::ZERO, 0.0));
+    }
+
+    #[test]
+    fn spawns_stay_in_the_speed_and_heading_ranges() {
+        let path = PerimeterPath::rect(Vec2::new(480.0, 720.0));
+        let params = SpawnParams::default();
+        let mut rng = GameRng::new(7);
+
+        for _ in 0..1000 {
+            let spawn = spawn_mob(&path, &params, &mut rng);
+            assert!(spawn.speed >= params.min_speed && spawn.speed < params.max_speed);
+
+            // The heading is at most `heading_spread` away from pointing straight in.
+            let (_, rotation) = path.sample(path_offset(spawn.position));
+            let inward = Vec2::from_angle(rotation + PI / 2.0);
+            let deviation = inward.dot(Vec2::from_angle(spawn.direction)).clamp(-1.0, 1.0).acos();
+            assert!(deviation <= params.heading_spread + 1e-4);
+        }
+    }
+
+    #[test]
+    fn spawns_use_the_minimum_speed_when_the_range_is_empty() {
+        let path = PerimeterPath::rect(Vec2::new(480.0, 720.0));
+        let params = SpawnParams {
+            min_speed: 200.0,
+            max_speed: 200.0,
+            heading_spread: 0.0,
+        };
+        let spawn = spawn_mob(&path, &params, &mut GameRng::new(1));
+        assert_eq!(spawn.speed, 200.0);
+    }
+
+    // The offset along the 480x720 rect of a point on its edge.
+    fn path_offset(position: Vec2) -> f32 {
+        if position.y <= 1e-3 {
+            position.x
+        } else if position.x >= 480.0 - 1e-3 {
+            480.0 + position.y
+        } else if position.y >= 720.0 - 1e-3 {
+            1200.0 + (480.0 - position.x)
+        } else {
+            1680.0 + (720.0 - position.y)
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use super::archetype::{pick_archetype, MobArchetype};
+use super::arena::{self, Arena};
+use super::behaviour::{BehaviourContext, MobBehaviour, MobState, Straight};
+use super::collision::Circle;
+use super::difficulty::{DifficultyCurve, DifficultyPreset};
+use super::lives::{HitOutcome, Lives, DEFAULT_LIVES};
+use super::math::Vec2;
+use super::player::{MoveInput, PlayerBody};
+use super::score::{Countdown, Score};
+use super::spawn::{spawn_mob, MobSpawn, PerimeterPath, SpawnParams};
+
+use rand::Rng;
+
+/// Everything needed to run a round without the engine.
+///
+/// The defaults match the values in the Godot scenes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldConfig {
+    /// Size of the play area, the viewport size in the game.
+    pub arena: Vec2,
+    pub player_speed: f32,
+    pub player_radius: f32,
+    /// Lives the player starts a round with.
+    pub lives: u32,
+    /// The kinds of mob that can spawn; their speed ranges are scaled by the difficulty.
+    pub archetypes: Vec<MobArchetype>,
+    /// Spawn rate, speed scale and heading spread by score.
+    pub difficulty: DifficultyCurve,
+    /// Delay between starting a round and the first mob/score tick (`StartTimer`).
+    pub start_delay: f32,
+    /// Seconds between score ticks (`ScoreTimer`).
+    pub score_interval: f32,
+    /// How far past the arena edge a mob may fly before it is removed.
+    pub despawn_margin: f32,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            arena: arena::DESIGN_SIZE,
+            player_speed: 400.0,
+            player_radius: 20.0,
+            lives: DEFAULT_LIVES,
+            archetypes: vec![MobArchetype::default()],
+            difficulty: DifficultyPreset::Normal.curve(),
+            start_delay: 2.0,
+            score_interval: 1.0,
+            despawn_margin: arena::DESPAWN_MARGIN,
+        }
+    }
+}
+
+/// A mob flying through the headless world.
+#[derive(Debug)]
+pub struct SimMob {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub radius: f32,
+    pub behaviour: Box<dyn MobBehaviour>,
+}
+
+/// Something that happened during a `World::step`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorldEvent {
+    MobSpawned(MobSpawn),
+    Scored(i32),
+    /// The player was hit and has this many lives left.
+    LifeLost(u32),
+    /// The player lost their last life.
+    PlayerHit,
+}
+
+/// A complete headless round: the player, the mobs, the timers and the score.
+///
+/// The Godot classes use the same pieces (`PlayerBody`, `spawn_mob`, `Score`)
+/// one at a time; `World` strings them together so a whole run can be stepped
+/// from a test.
+#[derive(Debug)]
+pub struct World {
+    pub config: WorldConfig,
+    pub player: PlayerBody,
+    pub lives: Lives,
+    pub mobs: Vec<SimMob>,
+    pub score: Score,
+    path: PerimeterPath,
+    start_timer: Countdown,
+    mob_timer: Countdown,
+    score_timer: Countdown,
+    game_over: bool,
+}
+
+impl World {
+    pub fn new(config: WorldConfig) -> Self {
+        Self {
+            player: PlayerBody::new(config.player_speed, config.arena),
+            lives: Lives::new(config.lives),
+            mobs: Vec::new(),
+            score: Score::default(),
+            path: PerimeterPath::rect(config.arena),
+            start_timer: Countdown::new(config.start_delay, true),
+            mob_timer: Countdown::new(config.difficulty.at(0).mob_interval, false),
+            score_timer: Countdown::new(config.score_interval, false),
+            game_over: false,
+            config,
+        }
+    }
+
+    /// Starts a new round with the player at `position`.
+    pub fn start(&mut self, position: Vec2) {
+        self.mobs.clear();
+        self.score.reset();
+        self.player.position = self.player.clamp(position);
+        self.lives = Lives::new(self.config.lives);
+        self.game_over = false;
+        self.mob_timer.set_wait_time(self.config.difficulty.at(0).mob_interval);
+        self.mob_timer.stop();
+        self.score_timer.stop();
+        self.start_timer.start();
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// Advances the world by `delta` seconds and reports what happened.
+    pub fn step<R: Rng + ?Sized>(&mut self, input: MoveInput, delta: f32, rng: &mut R) -> Vec<WorldEvent> {
+        let mut events = Vec::new();
+
+        if self.game_over {
+            return events;
+        }
+
+        // The start timer kicks off the mob and score timers, like `on_start_timer_timeout`.
+        if self.start_timer.step(delta) > 0 {
+            self.mob_timer.start();
+            self.score_timer.start();
+        }
+
+        for _ in 0..self.score_timer.step(delta) {
+            let score = self.score.tick();
+            // The spawn rate follows the difficulty curve from the next spawn on.
+            self.mob_timer
+                .set_wait_time(self.config.difficulty.at(score).mob_interval);
+            events.push(WorldEvent::Scored(score));
+        }
+
+        let difficulty = self.config.difficulty.at(self.score.value());
+        for _ in 0..self.mob_timer.step(delta) {
+            let Some(archetype) = pick_archetype(&self.config.archetypes, self.score.value(), rng) else {
+                continue;
+            };
+            let base = SpawnParams {
+                min_speed: archetype.min_speed,
+                max_speed: archetype.max_speed,
+                ..SpawnParams::default()
+            };
+            let spawn = spawn_mob(&self.path, &difficulty.apply(&base), rng);
+            self.mobs.push(SimMob {
+                position: spawn.position,
+                velocity: spawn.velocity(),
+                radius: archetype.collision_radius * archetype.scale,
+                behaviour: archetype.movement.behaviour(),
+            });
+            events.push(WorldEvent::MobSpawned(spawn));
+        }
+
+        self.player.step(input, delta);
+        self.lives.step(delta);
+
+        // Let each mob's behaviour steer it, then move it.
+        let context = BehaviourContext {
+            player: Some(self.player.position),
+            arena: self.config.arena,
+        };
+        let mut splits = Vec::new();
+        for mob in &mut self.mobs {
+            let state = MobState {
+                position: mob.position,
+                velocity: mob.velocity,
+            };
+            let steering = mob.behaviour.steer(&state, &context, delta);
+            mob.velocity = steering.velocity;
+            mob.position += mob.velocity * delta;
+
+            // The second half of a split mob flies straight.
+            if let Some(velocity) = steering.split {
+                splits.push(SimMob {
+                    position: mob.position,
+                    velocity,
+                    radius: mob.radius,
+                    behaviour: Box::new(Straight),
+                });
+            }
+        }
+        self.mobs.extend(splits);
+
+        // Drop mobs that have left the arena for good.
+        let margin = self.config.despawn_margin;
+        let arena = Arena::new(self.config.arena);
+        self.mobs.retain(|mob| arena.contains(mob.position, margin));
+
+        let player = Circle::new(self.player.position, self.config.player_radius);
+        if self
+            .mobs
+            .iter()
+            .any(|mob| player.overlaps(&Circle::new(mob.position, mob.radius)))
+        {
+            match self.lives.hit() {
+                HitOutcome::Ignored => {}
+                HitOutcome::LifeLost { remaining } => events.push(WorldEvent::LifeLost(remaining)),
+                HitOutcome::OutOfLives => {
+                    self.game_over();
+                    events.push(WorldEvent::PlayerHit);
+                }
+            }
+        }
+
+        events
+    }
+
+    fn game_over(&mut self) {
+        self.game_over = true;
+        self.mob_timer.stop();
+        self.score_timer.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::rng::GameRng;
+
+    const DELTA: f32 = 1.0 / 60.0;
+
+    // Runs a round from `seed`, zigzagging left and right, for at most ten minutes of game time.
+    fn run(seed: u64) -> (Vec<WorldEvent>, u32) {
+        let mut rng = GameRng::new(seed);
+        let mut world = World::new(WorldConfig::default());
+        world.start(Vec2::new(240.0, 450.0));
+
+        let mut events = Vec::new();
+        let mut frames = 0;
+        while !world.is_game_over() && frames < 60 * 600 {
+            let input = MoveInput {
+                left: frames % 120 < 60,
+                right: frames % 120 >= 60,
+                ..MoveInput::default()
+            };
+            events.extend(world.step(input, DELTA, &mut rng));
+            frames += 1;
+        }
+        (events, frames)
+    }
+
+    #[test]
+    fn seeded_runs_repeat_exactly() {
+        assert_eq!(run(42), run(42));
+    }
+
+    #[test]
+    fn runs_reach_game_over() {
+        let (events, _) = run(1);
+        assert_eq!(events.last(), Some(&WorldEvent::PlayerHit));
+        assert!(events.iter().any(|event| matches!(event, WorldEvent::MobSpawned(_))));
+        let lives_lost = events
+            .iter()
+            .filter(|event| matches!(event, WorldEvent::LifeLost(_)))
+            .count();
+        assert_eq!(lives_lost as u32, WorldConfig::default().lives - 1);
+    }
+
+    #[test]
+    fn nothing_spawns_before_the_start_delay() {
+        let mut rng = GameRng::new(3);
+        let mut world = World::new(WorldConfig::default());
+        world.start(Vec2::new(240.0, 450.0));
+
+        let frames = (WorldConfig::default().start_delay / DELTA) as usize - 1;
+        for _ in 0..frames {
+            assert!(world.step(MoveInput::default(), DELTA, &mut rng).is_empty());
+        }
+    }
+
+    #[test]
+    fn the_player_and_mobs_stay_in_bounds() {
+        // Fuzz the input: random presses every frame for several seeds.
+        for seed in 0..8 {
+            let mut rng = GameRng::new(seed);
+            let mut input_rng = GameRng::new(seed + 100);
+            let mut world = World::new(WorldConfig::default());
+            world.start(Vec2::new(240.0, 450.0));
+
+            for _ in 0..60 * 60 {
+                let input = MoveInput {
+                    left: input_rng.random(),
+                    right: input_rng.random(),
+                    up: input_rng.random(),
+                    down: input_rng.random(),
+                    ..MoveInput::default()
+                };
+                world.step(input, DELTA, &mut rng);
+
+                let arena = Arena::new(world.config.arena);
+                assert!(arena.contains(world.player.position, 0.0));
+                for mob in &world.mobs {
+                    assert!(arena.contains(mob.position, world.config.despawn_margin));
+                }
+                if world.is_game_over() {
+                    break;
+                }
+            }
+        }
+    }
+}
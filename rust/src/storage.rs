@@ -0,0 +1,47 @@
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{DirAccess, FileAccess};
+use godot::global::Error;
+use godot::prelude::*;
+
+/// Reads the whole file at `path` (e.g. "user://last_run.replay").
+///
+/// Returns `None` if the file does not exist or cannot be opened.
+pub fn read_bytes(path: &str) -> Option<Vec<u8>> {
+    if !FileAccess::file_exists(path) {
+        return None;
+    }
+
+    let file = FileAccess::open(path, ModeFlags::READ)?;
+    let length = file.get_length() as i64;
+    Some(file.get_buffer(length).to_vec())
+}
+
+/// Writes `bytes` to `path`, replacing any existing file.
+///
+/// The data is written to a temporary file first and then renamed over the
+/// old one, so a crash mid-write never leaves a half written file behind.
+pub fn write_bytes(path: &str, bytes: &[u8]) -> bool {
+    let temp_path = format!("{path}.tmp");
+
+    let Some(mut file) = FileAccess::open(&temp_path, ModeFlags::WRITE) else {
+        godot_error!("Could not open {temp_path} for writing: {:?}", FileAccess::get_open_error());
+        return false;
+    };
+
+    file.store_buffer(&PackedByteArray::from(bytes));
+    let error = file.get_error();
+    file.close();
+
+    if error != Error::OK {
+        godot_error!("Could not write {temp_path}: {error:?}");
+        return false;
+    }
+
+    let error = DirAccess::rename_absolute(&temp_path, path);
+    if error != Error::OK {
+        godot_error!("Could not replace {path}: {error:?}");
+        return false;
+    }
+
+    true
+}
@@ -0,0 +1,144 @@
+use crate::sim::bindings::Action;
+
+use godot::classes::{
+    Control, DisplayServer, IControl, Input, InputEvent, InputEventScreenDrag, InputEventScreenTouch,
+};
+use godot::prelude::*;
+
+/// Radius in pixels of the joystick's ring. Dragging the knob to the edge moves at full speed.
+const RADIUS: f32 = 72.0;
+
+/// Radius in pixels of the knob.
+const KNOB_RADIUS: f32 = 28.0;
+
+/// Colour of the ring, faint so it does not hide the mobs.
+const RING_COLOR: Color = Color::from_rgba(1.0, 1.0, 1.0, 0.15);
+
+/// Colour of the knob.
+const KNOB_COLOR: Color = Color::from_rgba(1.0, 1.0, 1.0, 0.35);
+
+/// An on-screen joystick for touch screens, such as phones playing the web build.
+///
+/// Touching anywhere in the control puts the joystick under the finger, and dragging
+/// presses the movement actions as far as the knob is pushed, as a gamepad stick would.
+/// The player reads those actions like any other input, so nothing else needs to know about it.
+#[derive(GodotClass)]
+#[class(base=Control)]
+pub struct VirtualJoystick {
+    // The index of the finger holding the joystick, if any.
+    touch: Option<i32>,
+    // Where the finger went down, in local coordinates, and how far it has been dragged from there.
+    centre: Vector2,
+    knob: Vector2,
+    // Turned off while the player follows the pointer, which takes touches too.
+    enabled: bool,
+
+    /// Shows the joystick even without a touch screen, e.g. to try it with emulated touches on a desktop.
+    #[export]
+    always_visible: bool,
+
+    base: Base<Control>,
+}
+
+#[godot_api]
+impl IControl for VirtualJoystick {
+    // This function initializes a new VirtualJoystick instance, with no finger on it.
+    fn init(base: Base<Control>) -> Self {
+        Self {
+            touch: None,
+            centre: Vector2::ZERO,
+            knob: Vector2::ZERO,
+            enabled: true,
+            always_visible: false,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        self.update_visibility();
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if !self.base().is_visible_in_tree() {
+            return;
+        }
+
+        // Work in the joystick's own coordinates, whatever the scaling of the window.
+        let event = self.base().make_input_local(&event).unwrap_or(event);
+
+        if let Ok(touch) = event.clone().try_cast::<InputEventScreenTouch>() {
+            let inside = Rect2::new(Vector2::ZERO, self.base().get_size()).has_point(touch.get_position());
+            if touch.is_pressed() && self.touch.is_none() && inside {
+                // A new finger takes hold of the joystick where it touches.
+                self.touch = Some(touch.get_index());
+                self.centre = touch.get_position();
+                self.set_knob(Vector2::ZERO);
+            } else if !touch.is_pressed() && self.touch == Some(touch.get_index()) {
+                // The finger lets go, and the player stops.
+                self.touch = None;
+                self.set_knob(Vector2::ZERO);
+            }
+        } else if let Ok(drag) = event.try_cast::<InputEventScreenDrag>() {
+            if self.touch == Some(drag.get_index()) {
+                self.set_knob(drag.get_position() - self.centre);
+            }
+        }
+    }
+
+    fn draw(&mut self) {
+        // Draw the ring and the knob under the finger, or at rest in the lower left while untouched.
+        let centre = match self.touch {
+            Some(_) => self.centre,
+            None => {
+                let size = self.base().get_size();
+                Vector2::new(size.x * 0.25, size.y * 0.5)
+            }
+        };
+        let knob = self.knob;
+        let mut base = self.base_mut();
+        base.draw_circle(centre, RADIUS, RING_COLOR);
+        base.draw_circle(centre + knob, KNOB_RADIUS, KNOB_COLOR);
+    }
+}
+
+impl VirtualJoystick {
+    /// Turns the joystick on or off, letting go of it if it is held.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled && self.touch.take().is_some() {
+            self.set_knob(Vector2::ZERO);
+        }
+        self.update_visibility();
+    }
+
+    /// Only shows the joystick on touch screens. Keyboards and gamepads have their own controls.
+    fn update_visibility(&mut self) {
+        let touch_screen = self.always_visible || DisplayServer::singleton().is_touchscreen_available();
+        let visible = self.enabled && touch_screen;
+        self.base_mut().set_visible(visible);
+    }
+
+    /// Moves the knob, at most to the edge of the ring, and presses the movement actions to match.
+    fn set_knob(&mut self, offset: Vector2) {
+        self.knob = offset.limit_length(Some(RADIUS));
+        let vector = self.knob / RADIUS;
+
+        // Each axis presses one of its two actions with the strength it is pushed with,
+        // and releases the other. Releasing both lets the keyboard and gamepad take over again.
+        let mut input = Input::singleton();
+        for (action, strength) in [
+            (Action::MoveLeft, -vector.x),
+            (Action::MoveRight, vector.x),
+            (Action::MoveUp, -vector.y),
+            (Action::MoveDown, vector.y),
+        ] {
+            if strength > 0.0 {
+                input.action_press_ex(action.name()).strength(strength).done();
+            } else {
+                input.action_release(action.name());
+            }
+        }
+
+        self.base_mut().queue_redraw();
+    }
+}